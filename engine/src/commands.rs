@@ -0,0 +1,237 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use crate::component::Component;
+use crate::scene::GameObject;
+use crate::scene::GameObjectId;
+use crate::scene::Scene;
+use crate::scene::SceneError;
+
+type Command = Box<dyn FnOnce(&Scene) -> Result<(), SceneError> + Send>;
+
+struct RecordedCommand {
+    key: CommandKey,
+    /// The active scene if `None`.
+    scene: Option<Arc<Scene>>,
+    command: Command,
+}
+
+/// (source, sequence number within the source)
+pub(crate) type CommandKey = (u64, u64);
+
+thread_local! {
+    static SOURCE: Cell<Option<CommandKey>> = const { Cell::new(None) };
+    static SCENE: RefCell<Option<Arc<Scene>>> = const { RefCell::new(None) };
+}
+
+/// The key of something recorded now on this thread, for sorting it
+/// deterministically. `unsourced` numbers what is recorded outside of engine
+/// jobs.
+pub(crate) fn next_key(unsourced: &AtomicU64) -> CommandKey {
+    match SOURCE.get() {
+        Some((source, sequence)) => {
+            SOURCE.set(Some((source, sequence + 1)));
+            (source, sequence)
+        }
+        None => (u64::MAX, unsourced.fetch_add(1, Ordering::Relaxed)),
+    }
+}
+
+/// Structural scene changes recorded from any job and applied by the engine
+/// at the next sync point, once the jobs of the current phase finished.
+///
+/// Game object ids are only unique within a scene, so every command is
+/// applied to the scene it was recorded for: the scene of the game object
+/// or query system job that recorded it, one set with
+/// [`with_scene`](Commands::with_scene), or else the active scene.
+///
+/// Commands are applied in a deterministic order: the engine gives every job
+/// a source number, and commands are sorted by source, then by the order
+/// they were recorded in. Commands recorded outside of engine jobs come last,
+/// in recording order.
+pub struct Commands {
+    queue: Mutex<Vec<RecordedCommand>>,
+    next_source: AtomicU64,
+    next_unsourced: AtomicU64,
+}
+
+impl Commands {
+    pub(crate) fn new() -> Self {
+        Self {
+            queue: Mutex::new(Vec::new()),
+            next_source: AtomicU64::new(0),
+            next_unsourced: AtomicU64::new(0),
+        }
+    }
+
+    /// Reserves `count` consecutive source numbers and returns the first.
+    pub(crate) fn reserve_sources(&self, count: usize) -> u64 {
+        self.next_source.fetch_add(count as u64, Ordering::Relaxed)
+    }
+
+    /// Runs `fun` with every command recorded on this thread attributed to
+    /// `source`.
+    pub(crate) fn with_source<R, F: FnOnce() -> R>(&self, source: u64, fun: F) -> R {
+        let previous = SOURCE.replace(Some((source, 0)));
+        let result = fun();
+        SOURCE.set(previous);
+
+        result
+    }
+
+    /// Runs `fun` with every command recorded on this thread applied to
+    /// `scene`.
+    pub fn with_scene<R, F: FnOnce() -> R>(&self, scene: &Arc<Scene>, fun: F) -> R {
+        let previous = SCENE.replace(Some(scene.clone()));
+        let result = fun();
+        SCENE.set(previous);
+
+        result
+    }
+
+    fn push<F>(&self, command: F)
+    where
+        F: FnOnce(&Scene) -> Result<(), SceneError> + Send + 'static,
+    {
+        let key = next_key(&self.next_unsourced);
+        let scene = SCENE.with_borrow(Option::clone);
+        self.queue.lock().unwrap().push(RecordedCommand {
+            key,
+            scene,
+            command: Box::new(command),
+        });
+    }
+
+    /// Adds a game object and passes it to `setup`, e.g. to add components.
+    pub fn spawn<F: FnOnce(&GameObject) + Send + 'static>(&self, setup: F) {
+        self.push(move |scene| {
+            let id = scene.add_game_object();
+            setup(&scene.game_object(id).unwrap());
+            Ok(())
+        });
+    }
+
+    /// Like [`spawn`](Commands::spawn), attaching the game object to
+    /// `parent`.
+    pub fn spawn_child<F: FnOnce(&GameObject) + Send + 'static>(
+        &self,
+        parent: GameObjectId,
+        setup: F,
+    ) {
+        self.push(move |scene| {
+            let id = scene.add_game_object();
+            let result = scene.attach(id, parent);
+            setup(&scene.game_object(id).unwrap());
+            result
+        });
+    }
+
+    /// See [`Scene::destroy`].
+    pub fn despawn(&self, id: GameObjectId) {
+        self.push(move |scene| scene.destroy(id));
+    }
+
+    pub fn add_component<T: Component>(&self, id: GameObjectId, component: T) {
+        self.push(move |scene| scene.add_component(id, component).map(|_| ()));
+    }
+
+    pub fn remove_component<T: Component>(&self, id: GameObjectId) {
+        self.push(move |scene| {
+            scene.remove_component::<T>(id);
+            Ok(())
+        });
+    }
+
+    pub fn attach(&self, child: GameObjectId, parent: GameObjectId) {
+        self.push(move |scene| scene.attach(child, parent));
+    }
+
+    pub fn detach(&self, child: GameObjectId) {
+        self.push(move |scene| scene.detach(child));
+    }
+
+    pub fn set_enabled(&self, id: GameObjectId, enabled: bool) {
+        self.push(move |scene| scene.set_enabled(id, enabled));
+    }
+
+    /// Records an arbitrary change.
+    pub fn run<F: FnOnce(&Scene) + Send + 'static>(&self, fun: F) {
+        self.push(move |scene| {
+            fun(scene);
+            Ok(())
+        });
+    }
+
+    /// Applies and clears the recorded commands, the ones recorded for no
+    /// scene in particular to `active_scene`, and returns the errors of the
+    /// ones that failed. Commands recorded while applying are kept for the
+    /// next call.
+    pub fn apply(&self, active_scene: &Scene) -> Vec<SceneError> {
+        let mut commands = std::mem::take(&mut *self.queue.lock().unwrap());
+        commands.sort_by_key(|command| command.key);
+
+        commands
+            .into_iter()
+            .filter_map(|command| {
+                (command.command)(command.scene.as_deref().unwrap_or(active_scene)).err()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Commands;
+    use crate::scene::Scene;
+
+    #[test]
+    fn test_commands_are_ordered_by_source() {
+        let scene = Scene::new();
+        let commands = Commands::new();
+
+        let first = commands.reserve_sources(2);
+        commands.with_source(first + 1, || {
+            commands.spawn(|game_object| game_object.set_name("c"));
+            commands.spawn(|game_object| game_object.set_name("d"));
+        });
+        commands.spawn(|game_object| game_object.set_name("e"));
+        commands.with_source(first, || {
+            commands.spawn(|game_object| game_object.set_name("a"));
+            commands.spawn(|game_object| game_object.set_name("b"));
+        });
+        assert!(scene.game_objects().is_empty());
+
+        assert!(commands.apply(&scene).is_empty());
+        let names: Vec<String> = scene
+            .game_objects()
+            .into_iter()
+            .map(|id| scene.game_object(id).unwrap().name())
+            .collect();
+        assert_eq!(vec!["a", "b", "c", "d", "e"], names);
+    }
+
+    #[test]
+    fn test_commands_are_applied_to_their_scene() {
+        let active_scene = Scene::new();
+        let other_scene = Scene::new();
+        let active_id = active_scene.add_game_object();
+        let other_id = other_scene.add_game_object();
+        assert_eq!(active_id, other_id);
+
+        let commands = Commands::new();
+        commands.with_scene(&other_scene, || commands.despawn(other_id));
+        assert!(commands.apply(&active_scene).is_empty());
+        assert!(!active_scene
+            .game_object(active_id)
+            .unwrap()
+            .is_pending_destroy());
+        assert!(other_scene
+            .game_object(other_id)
+            .unwrap()
+            .is_pending_destroy());
+    }
+}
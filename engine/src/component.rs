@@ -1,32 +1,92 @@
-use std::sync::Arc;
-use std::sync::Weak;
+use std::any::Any;
+use std::time::Duration;
 
 use crate::scene::GameObject;
 use crate::EngineContext;
 
-pub trait LogicComponentFn: Fn(&EngineContext, &GameObject) + Send + Sync + 'static {}
+/// Any thread-safe owned type can be stored as a component; see
+/// [`GameObject::add_component`].
+pub trait Component: Send + Sync + 'static {}
 
-impl<T> LogicComponentFn for T where T: Fn(&EngineContext, &GameObject) + Send + Sync + 'static {}
+impl<T> Component for T where T: Send + Sync + 'static {}
 
+/// Per-object logic with lifecycle hooks. Each frame the engine calls, for
+/// every game object and in the order the behaviours were added:
+///
+/// 1. `on_enable` when the object became active and `on_disable` when it
+///    became inactive, then `on_start` once before the first update;
+/// 2. `on_fixed_update` for each fixed step of the frame;
+/// 3. `on_update`, followed by the systems;
+/// 4. `on_late_update`, after which the transforms are updated;
+/// 5. `on_disable` and `on_destroy` for destroyed objects, before removing
+///    them.
+///
+/// Different game objects are processed in parallel, but the engine never
+/// runs hooks of the same behaviour concurrently, so they get `&mut self`.
+/// Use [`GameObject::get_component`] or [`GameObject::query`] for the data
+/// components of the same game object and [`GameObject::with_behaviour`] for
+/// its other behaviours.
+#[allow(unused_variables)]
+pub trait Behaviour: Any + Send {
+    fn on_start(&mut self, engine_context: &EngineContext, game_object: &GameObject) {}
+
+    fn on_enable(&mut self, engine_context: &EngineContext, game_object: &GameObject) {}
+
+    fn on_disable(&mut self, engine_context: &EngineContext, game_object: &GameObject) {}
+
+    fn on_fixed_update(
+        &mut self,
+        engine_context: &EngineContext,
+        game_object: &GameObject,
+        fixed_delta_time: Duration,
+    ) {
+    }
+
+    fn on_update(
+        &mut self,
+        engine_context: &EngineContext,
+        game_object: &GameObject,
+        delta_time: Duration,
+    ) {
+    }
+
+    fn on_late_update(
+        &mut self,
+        engine_context: &EngineContext,
+        game_object: &GameObject,
+        delta_time: Duration,
+    ) {
+    }
+
+    fn on_destroy(&mut self, engine_context: &EngineContext, game_object: &GameObject) {}
+}
+
+pub trait LogicComponentFn: FnMut(&EngineContext, &GameObject, Duration) + Send + 'static {}
+
+impl<T> LogicComponentFn for T where T: FnMut(&EngineContext, &GameObject, Duration) + Send + 'static
+{}
+
+/// A [`Behaviour`] running a closure on update.
 pub struct LogicComponent {
-    game_object: Weak<GameObject>,
     fun: Box<dyn LogicComponentFn>,
 }
 
 impl LogicComponent {
-    pub fn new<T>(game_object: Weak<GameObject>, fun: T) -> Arc<Self>
+    pub fn new<T>(fun: T) -> Self
     where
         T: LogicComponentFn,
     {
-        Arc::new(Self {
-            game_object,
-            fun: Box::new(fun),
-        })
+        Self { fun: Box::new(fun) }
     }
+}
 
-    pub fn run(&self, engine_context: &EngineContext) {
-        if let Some(game_object) = self.game_object.upgrade() {
-            (self.fun)(engine_context, &game_object);
-        }
+impl Behaviour for LogicComponent {
+    fn on_update(
+        &mut self,
+        engine_context: &EngineContext,
+        game_object: &GameObject,
+        delta_time: Duration,
+    ) {
+        (self.fun)(engine_context, game_object, delta_time);
     }
 }
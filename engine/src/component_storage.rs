@@ -0,0 +1,480 @@
+use std::any::Any;
+use std::any::TypeId;
+use std::cell::UnsafeCell;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::ops::Deref;
+use std::ops::DerefMut;
+use std::sync::Arc;
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::sync::RwLock;
+use std::thread;
+use std::thread::ThreadId;
+
+use crate::component::Component;
+use crate::query::ComponentAccess;
+use crate::scene::GameObjectId;
+
+/// A sparse set holding the components of one type, densely packed for fast
+/// iteration and indexed by game object for O(1) lookup.
+pub struct ComponentStorage<T> {
+    sparse: Vec<Option<usize>>,
+    ids: Vec<GameObjectId>,
+    components: Vec<T>,
+}
+
+impl<T> ComponentStorage<T> {
+    fn new() -> Self {
+        Self {
+            sparse: Vec::new(),
+            ids: Vec::new(),
+            components: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    fn dense_index(&self, id: GameObjectId) -> Option<usize> {
+        let index = (*self.sparse.get(id.index() as usize)?)?;
+        (self.ids[index] == id).then_some(index)
+    }
+
+    /// Returns the component it replaced, if any.
+    pub fn insert(&mut self, id: GameObjectId, component: T) -> Option<T> {
+        if let Some(index) = self.dense_index(id) {
+            return Some(std::mem::replace(&mut self.components[index], component));
+        }
+
+        let sparse_index = id.index() as usize;
+        if self.sparse.len() <= sparse_index {
+            self.sparse.resize(sparse_index + 1, None);
+        }
+        self.sparse[sparse_index] = Some(self.components.len());
+        self.ids.push(id);
+        self.components.push(component);
+
+        None
+    }
+
+    pub fn remove(&mut self, id: GameObjectId) -> Option<T> {
+        let index = self.dense_index(id)?;
+        self.sparse[id.index() as usize] = None;
+        self.ids.swap_remove(index);
+        let component = self.components.swap_remove(index);
+        if let Some(moved) = self.ids.get(index) {
+            self.sparse[moved.index() as usize] = Some(index);
+        }
+
+        Some(component)
+    }
+
+    pub fn contains(&self, id: GameObjectId) -> bool {
+        self.dense_index(id).is_some()
+    }
+
+    pub fn get(&self, id: GameObjectId) -> Option<&T> {
+        self.dense_index(id).map(|index| &self.components[index])
+    }
+
+    pub fn get_mut(&mut self, id: GameObjectId) -> Option<&mut T> {
+        self.dense_index(id)
+            .map(|index| &mut self.components[index])
+    }
+
+    pub fn ids(&self) -> &[GameObjectId] {
+        &self.ids
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (GameObjectId, &T)> {
+        self.ids.iter().copied().zip(self.components.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (GameObjectId, &mut T)> {
+        self.ids.iter().copied().zip(self.components.iter_mut())
+    }
+}
+
+#[derive(Default)]
+struct LockState {
+    readers: HashMap<TypeId, HashMap<ThreadId, usize>>,
+    writers: HashMap<TypeId, ThreadId>,
+    waiting: HashMap<ThreadId, Vec<ComponentAccess>>,
+}
+
+impl LockState {
+    fn holds_conflicting(&self, thread: ThreadId, access: &ComponentAccess) -> bool {
+        self.writers.get(&access.type_id()) == Some(&thread)
+            || access.is_write()
+                && self
+                    .readers
+                    .get(&access.type_id())
+                    .is_some_and(|readers| readers.contains_key(&thread))
+    }
+
+    /// The other threads holding storages in a way that conflicts with
+    /// `accesses`.
+    fn blockers(&self, thread: ThreadId, accesses: &[ComponentAccess]) -> HashSet<ThreadId> {
+        let mut blockers = HashSet::new();
+        for access in accesses {
+            blockers.extend(self.writers.get(&access.type_id()));
+            if access.is_write() {
+                if let Some(readers) = self.readers.get(&access.type_id()) {
+                    blockers.extend(readers.keys());
+                }
+            }
+        }
+        blockers.remove(&thread);
+
+        blockers
+    }
+
+    /// Whether the threads `thread` would wait for are, directly or not,
+    /// waiting for `thread`.
+    fn would_deadlock(&self, thread: ThreadId, accesses: &[ComponentAccess]) -> bool {
+        let mut stack: Vec<ThreadId> = self.blockers(thread, accesses).into_iter().collect();
+        let mut visited = HashSet::new();
+        while let Some(blocker) = stack.pop() {
+            if blocker == thread {
+                return true;
+            }
+            if visited.insert(blocker) {
+                if let Some(accesses) = self.waiting.get(&blocker) {
+                    stack.extend(self.blockers(blocker, accesses));
+                }
+            }
+        }
+
+        false
+    }
+}
+
+/// Tracks which threads read or write the component storages of one scene.
+/// It replaces a lock per storage, so that one call can lock every storage
+/// it needs at once, a thread can keep reading a storage it already reads,
+/// and a wait that would deadlock panics instead of hanging.
+#[derive(Default)]
+struct LockTable {
+    state: Mutex<LockState>,
+    released: Condvar,
+}
+
+impl LockTable {
+    /// Blocks until this thread holds all of `accesses`. Panics if this
+    /// thread already holds one of the storages in a conflicting way, e.g.
+    /// writes a `T` while reading another `T`, or if waiting would deadlock.
+    fn acquire(&self, accesses: &[ComponentAccess]) {
+        let thread = thread::current().id();
+        let mut state = self.state.lock().unwrap();
+        if let Some(access) = accesses
+            .iter()
+            .find(|access| state.holds_conflicting(thread, access))
+        {
+            drop(state);
+            panic!(
+                "{} components are accessed while this thread holds a conflicting guard or \
+                 query on them",
+                access.type_name()
+            );
+        }
+
+        while !state.blockers(thread, accesses).is_empty() {
+            if state.would_deadlock(thread, accesses) {
+                state.waiting.remove(&thread);
+                drop(state);
+                panic!(
+                    "waiting for the {} components would deadlock with another thread; access \
+                     the components together through one query instead",
+                    accesses
+                        .iter()
+                        .map(|access| access.type_name())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            }
+            state.waiting.insert(thread, accesses.to_vec());
+            state = self.released.wait(state).unwrap();
+        }
+
+        state.waiting.remove(&thread);
+        for access in accesses {
+            if access.is_write() {
+                state.writers.insert(access.type_id(), thread);
+            } else {
+                *state
+                    .readers
+                    .entry(access.type_id())
+                    .or_default()
+                    .entry(thread)
+                    .or_default() += 1;
+            }
+        }
+    }
+
+    fn release(&self, access: &ComponentAccess) {
+        let thread = thread::current().id();
+        let mut state = self.state.lock().unwrap();
+        if access.is_write() {
+            state.writers.remove(&access.type_id());
+        } else if let Some(readers) = state.readers.get_mut(&access.type_id()) {
+            let count = readers.get_mut(&thread).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                readers.remove(&thread);
+            }
+        }
+        drop(state);
+
+        self.released.notify_all();
+    }
+}
+
+/// A [`ComponentStorage`] whose access is tracked by the [`LockTable`] of
+/// its scene.
+pub struct StorageCell<T: Component> {
+    locks: Arc<LockTable>,
+    storage: UnsafeCell<ComponentStorage<T>>,
+}
+
+// The lock table hands out either one writer or any number of readers.
+unsafe impl<T: Component> Sync for StorageCell<T> {}
+
+impl<T: Component> StorageCell<T> {
+    /// Locks the storage for reading until the guard is dropped.
+    pub fn read(&self) -> StorageRef<'_, T> {
+        self.locks.acquire(&[ComponentAccess::read::<T>()]);
+        StorageRef { cell: self }
+    }
+
+    /// Locks the storage for writing until the guard is dropped.
+    pub fn write(&self) -> StorageMut<'_, T> {
+        self.locks.acquire(&[ComponentAccess::write::<T>()]);
+        StorageMut { cell: self }
+    }
+
+    /// Locks the storages of all `accesses`, which must belong to the same
+    /// scene as this one, at once. Each must then be wrapped in a guard
+    /// with [`StorageRef::locked`] or [`StorageMut::locked`].
+    pub(crate) fn lock_all(&self, accesses: &[ComponentAccess]) {
+        self.locks.acquire(accesses);
+    }
+}
+
+trait ErasedStorage: Send + Sync {
+    fn remove(&self, id: GameObjectId);
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync>;
+}
+
+impl<T: Component> ErasedStorage for StorageCell<T> {
+    fn remove(&self, id: GameObjectId) {
+        self.write().remove(id);
+    }
+
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+}
+
+pub type SharedComponentStorage<T> = Arc<StorageCell<T>>;
+
+pub struct StorageRef<'s, T: Component> {
+    cell: &'s StorageCell<T>,
+}
+
+impl<'s, T: Component> StorageRef<'s, T> {
+    /// # Safety
+    ///
+    /// This thread must have locked the storage for reading, e.g. through
+    /// [`StorageCell::lock_all`]. The guard releases it.
+    pub(crate) unsafe fn locked(cell: &'s StorageCell<T>) -> Self {
+        Self { cell }
+    }
+}
+
+impl<T: Component> Deref for StorageRef<'_, T> {
+    type Target = ComponentStorage<T>;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.cell.storage.get() }
+    }
+}
+
+impl<T: Component> Drop for StorageRef<'_, T> {
+    fn drop(&mut self) {
+        self.cell.locks.release(&ComponentAccess::read::<T>());
+    }
+}
+
+pub struct StorageMut<'s, T: Component> {
+    cell: &'s StorageCell<T>,
+}
+
+impl<'s, T: Component> StorageMut<'s, T> {
+    /// # Safety
+    ///
+    /// This thread must have locked the storage for writing, e.g. through
+    /// [`StorageCell::lock_all`]. The guard releases it.
+    pub(crate) unsafe fn locked(cell: &'s StorageCell<T>) -> Self {
+        Self { cell }
+    }
+}
+
+impl<T: Component> Deref for StorageMut<'_, T> {
+    type Target = ComponentStorage<T>;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.cell.storage.get() }
+    }
+}
+
+impl<T: Component> DerefMut for StorageMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.cell.storage.get() }
+    }
+}
+
+impl<T: Component> Drop for StorageMut<'_, T> {
+    fn drop(&mut self) {
+        self.cell.locks.release(&ComponentAccess::write::<T>());
+    }
+}
+
+/// One [`ComponentStorage`] per component type. Each is locked on its own,
+/// so jobs touching different types never contend.
+///
+/// A thread may hold several guards and run queries inside queries: it can
+/// read a type it already reads, and it waits for types other threads hold.
+/// It panics when it accesses a type it writes, or writes a type it reads,
+/// since that would deadlock on the spot. It also panics instead of waiting
+/// when the threads it waits for are in turn waiting for it, e.g. when two
+/// jobs lock the same two types in opposite order. Locking the types
+/// together, through a [`Query`](crate::query::Query) or
+/// [`GameObject::query`](crate::scene::GameObject::query), never deadlocks.
+pub struct Components {
+    locks: Arc<LockTable>,
+    storages: RwLock<HashMap<TypeId, Arc<dyn ErasedStorage>>>,
+}
+
+impl Components {
+    pub fn new() -> Self {
+        Self {
+            locks: Arc::new(LockTable::default()),
+            storages: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn storage<T: Component>(&self) -> SharedComponentStorage<T> {
+        let type_id = TypeId::of::<T>();
+        let storage = self.storages.read().unwrap().get(&type_id).cloned();
+        let storage = storage.unwrap_or_else(|| {
+            self.storages
+                .write()
+                .unwrap()
+                .entry(type_id)
+                .or_insert_with(|| {
+                    Arc::new(StorageCell {
+                        locks: self.locks.clone(),
+                        storage: UnsafeCell::new(ComponentStorage::<T>::new()),
+                    })
+                })
+                .clone()
+        });
+
+        storage.into_any().downcast().unwrap()
+    }
+
+    /// Removes every component of the game object.
+    pub fn remove_all(&self, id: GameObjectId) {
+        let storages: Vec<_> = self.storages.read().unwrap().values().cloned().collect();
+        for storage in storages {
+            storage.remove(id);
+        }
+    }
+}
+
+impl Default for Components {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Shared access to one component. Other threads can't write any `T` of the
+/// scene until it is dropped; see [`Components`] for what the holding
+/// thread may still access.
+pub struct ComponentRef<T: Component> {
+    storage: SharedComponentStorage<T>,
+    index: usize,
+}
+
+impl<T: Component> ComponentRef<T> {
+    pub(crate) fn new(storage: SharedComponentStorage<T>, id: GameObjectId) -> Option<Self> {
+        let guard = storage.read();
+        let index = guard.dense_index(id)?;
+        // Dropping the ComponentRef releases the lock instead.
+        std::mem::forget(guard);
+
+        Some(Self { storage, index })
+    }
+}
+
+impl<T: Component> Deref for ComponentRef<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        let storage = unsafe { &*self.storage.storage.get() };
+        &storage.components[self.index]
+    }
+}
+
+impl<T: Component> Drop for ComponentRef<T> {
+    fn drop(&mut self) {
+        self.storage.locks.release(&ComponentAccess::read::<T>());
+    }
+}
+
+/// Exclusive access to one component. Other threads can't access any `T`
+/// of the scene until it is dropped, and the holding thread must not access
+/// any other `T` meanwhile; see [`Components`].
+pub struct ComponentMut<T: Component> {
+    storage: SharedComponentStorage<T>,
+    index: usize,
+}
+
+impl<T: Component> ComponentMut<T> {
+    pub(crate) fn new(storage: SharedComponentStorage<T>, id: GameObjectId) -> Option<Self> {
+        let guard = storage.write();
+        let index = guard.dense_index(id)?;
+        // See ComponentRef::new.
+        std::mem::forget(guard);
+
+        Some(Self { storage, index })
+    }
+}
+
+impl<T: Component> Deref for ComponentMut<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        let storage = unsafe { &*self.storage.storage.get() };
+        &storage.components[self.index]
+    }
+}
+
+impl<T: Component> DerefMut for ComponentMut<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        let storage = unsafe { &mut *self.storage.storage.get() };
+        &mut storage.components[self.index]
+    }
+}
+
+impl<T: Component> Drop for ComponentMut<T> {
+    fn drop(&mut self) {
+        self.storage.locks.release(&ComponentAccess::write::<T>());
+    }
+}
@@ -0,0 +1,295 @@
+use std::any::Any;
+use std::any::TypeId;
+use std::collections::BTreeMap;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::RwLock;
+use std::sync::Weak;
+
+use crate::commands::next_key;
+use crate::commands::CommandKey;
+use crate::scene::GameObject;
+use crate::EngineContext;
+
+pub trait Event: Send + Sync + 'static {}
+
+impl<T: Send + Sync + 'static> Event for T {}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Delivery {
+    /// At the next sync point, after the jobs of the current phase or system
+    /// batch finished.
+    NextStage,
+    /// At the start of the next frame.
+    NextFrame,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct SubscriptionId(u64);
+
+type Handler<T> = Arc<dyn Fn(&EngineContext, Option<&GameObject>, &T) + Send + Sync>;
+type Delivered<T> = Vec<(Option<Arc<GameObject>>, T)>;
+
+struct Subscriber<T> {
+    id: SubscriptionId,
+    /// Only events sent to this game object if set.
+    target: Option<Weak<GameObject>>,
+    handler: Handler<T>,
+}
+
+struct PendingEvent<T> {
+    key: CommandKey,
+    delivery: Delivery,
+    target: Option<Arc<GameObject>>,
+    event: T,
+}
+
+/// The events of one type.
+struct Channel<T> {
+    pending: Mutex<Vec<PendingEvent<T>>>,
+    subscribers: RwLock<Vec<Subscriber<T>>>,
+    /// The events delivered during the previous frame, readable by anyone.
+    front: RwLock<Delivered<T>>,
+    /// The events delivered so far during the current frame.
+    back: Mutex<Delivered<T>>,
+}
+
+impl<T: Event> Channel<T> {
+    fn new() -> Self {
+        Self {
+            pending: Mutex::new(Vec::new()),
+            subscribers: RwLock::new(Vec::new()),
+            front: RwLock::new(Vec::new()),
+            back: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+trait ErasedChannel: Send + Sync {
+    fn deliver(&self, engine_context: &EngineContext, delivery: Delivery);
+    fn swap_buffers(&self);
+    fn unsubscribe(&self, id: SubscriptionId) -> bool;
+    fn forget_destroyed(&self);
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync>;
+}
+
+impl<T: Event> ErasedChannel for Channel<T> {
+    fn deliver(&self, engine_context: &EngineContext, delivery: Delivery) {
+        let mut events = {
+            let mut pending = self.pending.lock().unwrap();
+            let (events, rest) = std::mem::take(&mut *pending)
+                .into_iter()
+                .partition(|event| event.delivery == delivery);
+            *pending = rest;
+            events
+        };
+        if events.is_empty() {
+            return;
+        }
+        events.sort_by_key(|event: &PendingEvent<T>| event.key);
+
+        let handlers: Vec<_> = self
+            .subscribers
+            .read()
+            .unwrap()
+            .iter()
+            .map(|subscriber| (subscriber.target.clone(), subscriber.handler.clone()))
+            .collect();
+        for event in &events {
+            for (target, handler) in &handlers {
+                let matches = match (target, &event.target) {
+                    (None, _) => true,
+                    (Some(target), Some(event_target)) => {
+                        target.as_ptr() == Arc::as_ptr(event_target)
+                    }
+                    (Some(_), None) => false,
+                };
+                if matches {
+                    handler(engine_context, event.target.as_deref(), &event.event);
+                }
+            }
+        }
+
+        self.back
+            .lock()
+            .unwrap()
+            .extend(events.into_iter().map(|event| (event.target, event.event)));
+    }
+
+    fn swap_buffers(&self) {
+        let delivered = std::mem::take(&mut *self.back.lock().unwrap());
+        *self.front.write().unwrap() = delivered;
+    }
+
+    fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        let mut subscribers = self.subscribers.write().unwrap();
+        let count = subscribers.len();
+        subscribers.retain(|subscriber| subscriber.id != id);
+
+        subscribers.len() != count
+    }
+
+    fn forget_destroyed(&self) {
+        self.subscribers.write().unwrap().retain(|subscriber| {
+            subscriber.target.as_ref().is_none_or(|target| {
+                target
+                    .upgrade()
+                    .is_some_and(|target| !target.is_pending_destroy())
+            })
+        });
+    }
+
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+}
+
+/// Typed events sent from any job and delivered by the engine at sync
+/// points. Subscribers are called one after another on the engine thread,
+/// in a deterministic order like [`Commands`](crate::commands::Commands);
+/// events they send are delivered at the next sync point at the earliest.
+/// Delivered events can also be read during the whole next frame.
+pub struct Events {
+    channels: RwLock<BTreeMap<TypeId, Arc<dyn ErasedChannel>>>,
+    next_subscription: AtomicU64,
+    next_unsourced: AtomicU64,
+}
+
+impl Events {
+    pub(crate) fn new() -> Self {
+        Self {
+            channels: RwLock::new(BTreeMap::new()),
+            next_subscription: AtomicU64::new(0),
+            next_unsourced: AtomicU64::new(0),
+        }
+    }
+
+    fn channel<T: Event>(&self) -> Arc<Channel<T>> {
+        let type_id = TypeId::of::<T>();
+        let channel = self.channels.read().unwrap().get(&type_id).cloned();
+        let channel = channel.unwrap_or_else(|| {
+            self.channels
+                .write()
+                .unwrap()
+                .entry(type_id)
+                .or_insert_with(|| Arc::new(Channel::<T>::new()))
+                .clone()
+        });
+
+        channel.into_any().downcast().unwrap()
+    }
+
+    fn channels(&self) -> Vec<Arc<dyn ErasedChannel>> {
+        self.channels.read().unwrap().values().cloned().collect()
+    }
+
+    fn push<T: Event>(&self, delivery: Delivery, target: Option<Arc<GameObject>>, event: T) {
+        let key = next_key(&self.next_unsourced);
+        self.channel::<T>()
+            .pending
+            .lock()
+            .unwrap()
+            .push(PendingEvent {
+                key,
+                delivery,
+                target,
+                event,
+            });
+    }
+
+    pub fn send<T: Event>(&self, delivery: Delivery, event: T) {
+        self.push(delivery, None, event);
+    }
+
+    /// Sends an event addressed to one game object. Subscribers to all
+    /// events of the type get it too.
+    pub fn send_to<T: Event>(&self, delivery: Delivery, target: &Arc<GameObject>, event: T) {
+        self.push(delivery, Some(target.clone()), event);
+    }
+
+    /// Calls `fun` with every event of type `T` and its target, if any.
+    pub fn subscribe<T, F>(&self, fun: F) -> SubscriptionId
+    where
+        T: Event,
+        F: Fn(&EngineContext, Option<&GameObject>, &T) + Send + Sync + 'static,
+    {
+        self.add_subscriber(None, Arc::new(fun))
+    }
+
+    /// Calls `fun` with the events of type `T` sent to `target`, until it is
+    /// destroyed.
+    pub fn subscribe_to<T, F>(&self, target: &Arc<GameObject>, fun: F) -> SubscriptionId
+    where
+        T: Event,
+        F: Fn(&EngineContext, &GameObject, &T) + Send + Sync + 'static,
+    {
+        self.add_subscriber(
+            Some(Arc::downgrade(target)),
+            Arc::new(move |engine_context, target: Option<&GameObject>, event| {
+                fun(engine_context, target.unwrap(), event)
+            }),
+        )
+    }
+
+    fn add_subscriber<T: Event>(
+        &self,
+        target: Option<Weak<GameObject>>,
+        handler: Handler<T>,
+    ) -> SubscriptionId {
+        let id = SubscriptionId(self.next_subscription.fetch_add(1, Ordering::Relaxed));
+        self.channel::<T>()
+            .subscribers
+            .write()
+            .unwrap()
+            .push(Subscriber {
+                id,
+                target,
+                handler,
+            });
+
+        id
+    }
+
+    /// Returns whether the subscription existed.
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        self.channels()
+            .into_iter()
+            .any(|channel| channel.unsubscribe(id))
+    }
+
+    /// Calls `fun` with every event of type `T` delivered during the
+    /// previous frame, in delivery order.
+    pub fn read<T: Event, F: FnMut(Option<&GameObject>, &T)>(&self, mut fun: F) {
+        for (target, event) in self.channel::<T>().front.read().unwrap().iter() {
+            fun(target.as_deref(), event);
+        }
+    }
+
+    /// Delivers the events sent for the next stage.
+    pub(crate) fn deliver(&self, engine_context: &EngineContext) {
+        for channel in self.channels() {
+            channel.deliver(engine_context, Delivery::NextStage);
+        }
+    }
+
+    /// Makes the events delivered during the previous frame readable and
+    /// delivers the events sent for the next frame.
+    pub(crate) fn begin_frame(&self, engine_context: &EngineContext) {
+        let channels = self.channels();
+        for channel in &channels {
+            channel.swap_buffers();
+        }
+        for channel in &channels {
+            channel.deliver(engine_context, Delivery::NextFrame);
+        }
+    }
+
+    /// Drops the subscriptions of destroyed game objects.
+    pub(crate) fn forget_destroyed(&self) {
+        for channel in self.channels() {
+            channel.forget_destroyed();
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use std::time::Duration;
+use std::time::Instant;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct FrameLoopConfig {
+    /// The duration of one simulation step.
+    pub fixed_delta_time: Duration,
+    /// The most fixed steps run in one frame. Simulation time that doesn't
+    /// fit is dropped, so a slow frame slows the game down instead of making
+    /// the next frames even slower.
+    pub max_fixed_steps: u32,
+    /// Frames per second [`FrameLoop::wait`] sleeps down to, unlimited if
+    /// `None`.
+    pub target_frame_rate: Option<u32>,
+}
+
+impl Default for FrameLoopConfig {
+    fn default() -> Self {
+        Self {
+            fixed_delta_time: Duration::from_secs(1) / 60,
+            max_fixed_steps: 5,
+            target_frame_rate: None,
+        }
+    }
+}
+
+/// What to run for one frame.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct FrameTiming {
+    /// The variable, scaled time since the previous frame.
+    pub delta_time: Duration,
+    pub fixed_steps: u32,
+    /// How far the frame is between the last fixed step and the next one, in
+    /// `[0, 1)`, for interpolating the simulated state when rendering.
+    pub alpha: f64,
+}
+
+/// Splits elapsed time into fixed simulation steps with an accumulator.
+pub struct FrameLoop {
+    config: FrameLoopConfig,
+    accumulator: Duration,
+    previous_frame: Option<Instant>,
+}
+
+impl FrameLoop {
+    /// Panics if `config.fixed_delta_time` or `config.max_fixed_steps` is
+    /// zero.
+    pub fn new(config: FrameLoopConfig) -> Self {
+        assert!(
+            !config.fixed_delta_time.is_zero(),
+            "the fixed delta time must not be zero"
+        );
+        assert!(
+            config.max_fixed_steps > 0,
+            "the maximum number of fixed steps must not be zero"
+        );
+
+        Self {
+            config,
+            accumulator: Duration::ZERO,
+            previous_frame: None,
+        }
+    }
+
+    pub fn config(&self) -> FrameLoopConfig {
+        self.config
+    }
+
+    /// Accounts for `elapsed` time of a frame.
+    pub fn advance(&mut self, elapsed: Duration) -> FrameTiming {
+        let fixed_delta_time = self.config.fixed_delta_time;
+        self.accumulator += elapsed;
+
+        let mut fixed_steps = 0;
+        while self.accumulator >= fixed_delta_time {
+            if fixed_steps == self.config.max_fixed_steps {
+                self.accumulator = Duration::from_nanos(
+                    (self.accumulator.as_nanos() % fixed_delta_time.as_nanos()) as u64,
+                );
+                break;
+            }
+            self.accumulator -= fixed_delta_time;
+            fixed_steps += 1;
+        }
+
+        FrameTiming {
+            delta_time: elapsed,
+            fixed_steps,
+            alpha: self.accumulator.as_secs_f64() / fixed_delta_time.as_secs_f64(),
+        }
+    }
+
+    /// Sleeps until the target frame rate allows the next frame, then
+    /// returns the real time since the previous call, zero on the first one.
+    pub fn wait(&mut self) -> Duration {
+        if let (Some(previous_frame), Some(target_frame_rate)) =
+            (self.previous_frame, self.config.target_frame_rate)
+        {
+            let frame_time = Duration::from_secs(1) / target_frame_rate.max(1);
+            if let Some(remaining) = frame_time.checked_sub(previous_frame.elapsed()) {
+                std::thread::sleep(remaining);
+            }
+        }
+
+        let now = Instant::now();
+        let elapsed = self
+            .previous_frame
+            .map_or(Duration::ZERO, |previous_frame| now - previous_frame);
+        self.previous_frame = Some(now);
+
+        elapsed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::FrameLoop;
+    use super::FrameLoopConfig;
+
+    #[test]
+    fn test_frame_loop() {
+        let mut frame_loop = FrameLoop::new(FrameLoopConfig {
+            fixed_delta_time: Duration::from_millis(20),
+            max_fixed_steps: 3,
+            target_frame_rate: None,
+        });
+
+        let timing = frame_loop.advance(Duration::from_millis(10));
+        assert_eq!(0, timing.fixed_steps);
+        assert_eq!(0.5, timing.alpha);
+
+        let timing = frame_loop.advance(Duration::from_millis(35));
+        assert_eq!(2, timing.fixed_steps);
+        assert_eq!(Duration::from_millis(35), timing.delta_time);
+        assert!((timing.alpha - 0.25).abs() < 1e-9);
+
+        // A hitch only runs as many steps as allowed and drops the rest.
+        let timing = frame_loop.advance(Duration::from_millis(1000));
+        assert_eq!(3, timing.fixed_steps);
+        assert!((timing.alpha - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    #[should_panic(expected = "the fixed delta time must not be zero")]
+    fn test_frame_loop_rejects_zero_fixed_delta_time() {
+        FrameLoop::new(FrameLoopConfig {
+            fixed_delta_time: Duration::ZERO,
+            ..FrameLoopConfig::default()
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "the maximum number of fixed steps must not be zero")]
+    fn test_frame_loop_rejects_zero_max_fixed_steps() {
+        FrameLoop::new(FrameLoopConfig {
+            max_fixed_steps: 0,
+            ..FrameLoopConfig::default()
+        });
+    }
+}
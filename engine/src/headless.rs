@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::frame_loop::FrameTiming;
+use crate::input_handler::Key;
+use crate::Engine;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Clock {
+    /// Every frame lasts the given time, however long it really takes, so
+    /// runs are reproducible.
+    Manual(Duration),
+    /// Frames follow the real time and the target frame rate, as with
+    /// [`Engine::tick`], e.g. for dedicated servers.
+    RealTime,
+}
+
+/// Runs an [`Engine`] without a window, feeding it scripted input.
+pub struct HeadlessRunner {
+    engine: Engine,
+    clock: Clock,
+    /// Key changes to apply at the start of a frame, by frame number.
+    input: BTreeMap<u64, Vec<(Key, bool)>>,
+    frame: u64,
+}
+
+impl HeadlessRunner {
+    /// Starts with a manual clock stepping by the fixed delta time of the
+    /// engine.
+    pub fn new(engine: Engine) -> Self {
+        let clock = Clock::Manual(engine.frame_loop_config().fixed_delta_time);
+        Self {
+            engine,
+            clock,
+            input: BTreeMap::new(),
+            frame: 0,
+        }
+    }
+
+    pub fn engine(&self) -> &Engine {
+        &self.engine
+    }
+
+    pub fn engine_mut(&mut self) -> &mut Engine {
+        &mut self.engine
+    }
+
+    pub fn into_engine(self) -> Engine {
+        self.engine
+    }
+
+    pub fn set_clock(&mut self, clock: Clock) {
+        self.clock = clock;
+    }
+
+    /// The number of frames run so far, which is also the number of the next
+    /// frame.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Presses or releases `key` at the start of frame number `frame`. Input
+    /// for frames that already ran is applied at the start of the next one.
+    pub fn schedule_input(&mut self, frame: u64, key: Key, pressed: bool) -> &mut Self {
+        self.input.entry(frame).or_default().push((key, pressed));
+        self
+    }
+
+    pub fn press(&mut self, frame: u64, key: Key) -> &mut Self {
+        self.schedule_input(frame, key, true)
+    }
+
+    pub fn release(&mut self, frame: u64, key: Key) -> &mut Self {
+        self.schedule_input(frame, key, false)
+    }
+
+    /// Runs one frame.
+    pub fn step(&mut self) -> FrameTiming {
+        let later = self.input.split_off(&(self.frame + 1));
+        let input_handler = self.engine.engine_context().input_handler();
+        for (key, pressed) in std::mem::replace(&mut self.input, later)
+            .into_values()
+            .flatten()
+        {
+            input_handler.key_state_changed(key, pressed);
+        }
+
+        self.frame += 1;
+        match self.clock {
+            Clock::Manual(frame_time) => self.engine.step(frame_time),
+            Clock::RealTime => self.engine.tick(),
+        }
+    }
+
+    pub fn run_frames(&mut self, count: u64) {
+        for _ in 0..count {
+            self.step();
+        }
+    }
+
+    /// Runs frames until `condition` holds after one of them, at most
+    /// `max_frames`. Returns the number of frames run, or `None` if the
+    /// condition never held.
+    pub fn run_until<F: FnMut(&Engine) -> bool>(
+        &mut self,
+        max_frames: u64,
+        mut condition: F,
+    ) -> Option<u64> {
+        for frames in 1..=max_frames {
+            self.step();
+            if condition(&self.engine) {
+                return Some(frames);
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::time::Duration;
+
+    use util::job::Scheduler;
+    use util::logger::create_logger;
+    use util::thread_pool_descriptor;
+
+    use super::Clock;
+    use super::HeadlessRunner;
+    use crate::input_handler::Key;
+    use crate::scene::Scene;
+    use crate::Engine;
+    use crate::EngineThreadCategory;
+
+    thread_pool_descriptor!(EngineThreadCategory, Logger: 1, GameObject: 2);
+
+    #[derive(Default)]
+    struct Position(f64);
+
+    #[test]
+    fn test_headless_runner() {
+        let scene = Scene::new();
+        let (_, logger_client) = create_logger(1, Box::new(io::sink()));
+        let engine = Engine::new(
+            Scheduler::new(ThreadPoolDescriptor {}),
+            logger_client,
+            scene.clone(),
+        );
+        let mut runner = HeadlessRunner::new(engine);
+        runner.set_clock(Clock::Manual(Duration::from_millis(100)));
+
+        let player = scene.game_object(scene.add_game_object()).unwrap();
+        player.add_component(Position::default());
+        player.add_logic_component(|engine_context, game_object, delta_time| {
+            if engine_context.input_handler().is_pressed(Key::D) {
+                game_object.get_component_mut::<Position>().unwrap().0 += delta_time.as_secs_f64();
+            }
+        });
+
+        runner.press(2, Key::D).release(5, Key::D);
+        runner.run_frames(2);
+        assert_eq!(0.0, player.get_component::<Position>().unwrap().0);
+
+        let frames = runner.run_until(10, |_| player.get_component::<Position>().unwrap().0 > 0.25);
+        assert_eq!(Some(3), frames);
+        runner.run_frames(5);
+        assert_eq!(10, runner.frame());
+        assert!((player.get_component::<Position>().unwrap().0 - 0.3).abs() < 1e-9);
+        assert_eq!(
+            Duration::from_secs(1),
+            runner.engine().engine_context().time().elapsed()
+        );
+    }
+}
@@ -1,9 +1,9 @@
-use std::collections::HashMap;
-use std::sync::Mutex;
-use std::sync::MutexGuard;
+use std::collections::BTreeMap;
+use std::sync::RwLock;
 use std::time::Duration;
 use std::time::Instant;
 
+use util::enum_map::EnumMap;
 use util::internal_mut_struct;
 use util::smart_enum;
 
@@ -20,25 +20,23 @@ enum KeyState {
 }
 
 struct InputHandlerImpl {
-    key_state_map: HashMap<Key, KeyState>,
+    key_state_map: EnumMap<Key, KeyState>,
+    /// The keys bound to every named action.
+    actions: BTreeMap<String, Vec<Key>>,
     now: Instant,
 }
 
 impl InputHandlerImpl {
     pub fn new() -> Self {
-        let mut key_state_map = HashMap::new();
-        for key in Key::values() {
-            key_state_map.insert(key, KeyState::Up);
-        }
-
         Self {
-            key_state_map,
+            key_state_map: EnumMap::new(|_| KeyState::Up),
+            actions: BTreeMap::new(),
             now: Instant::now(),
         }
     }
 
     pub fn key_state_changed(&mut self, key: Key, pressed: bool) {
-        let key_state = self.key_state_map.get_mut(&key).unwrap();
+        let key_state = self.key_state_map.get_mut(key);
         *key_state = match *key_state {
             KeyState::Up => {
                 if pressed {
@@ -58,39 +56,75 @@ impl InputHandlerImpl {
     }
 }
 
-internal_mut_struct!(InputHandler, InputHandlerImpl);
+internal_mut_struct!(RwLock; InputHandler, InputHandlerImpl);
 
 impl InputHandler {
     pub fn new() -> Self {
         Self {
-            inner: Mutex::new(InputHandlerImpl::new()),
+            inner: RwLock::new(InputHandlerImpl::new()),
         }
     }
 
     pub fn update(&self, delta_time: Duration) {
-        self.lock_inner().now += delta_time;
+        self.write_inner().now += delta_time;
     }
 
     pub fn key_state_changed(&self, key: Key, pressed: bool) {
-        self.lock_inner().key_state_changed(key, pressed);
+        self.write_inner().key_state_changed(key, pressed);
     }
 
     pub fn is_pressed(&self, key: Key) -> bool {
-        let key_state = *self.lock_inner().key_state_map.get(&key).unwrap();
+        let key_state = self.read_inner().key_state_map[key];
         key_state != KeyState::Up
     }
 
     pub fn is_held(&self, key: Key) -> bool {
         const HOLD_DURATION: Duration = Duration::from_secs(1);
 
-        let inner = self.lock_inner();
-        let key_state = inner.key_state_map.get(&key).unwrap();
+        let inner = self.read_inner();
+        let key_state = inner.key_state_map.get(key);
         if let KeyState::Down(pressed_since) = *key_state {
             inner.now - pressed_since > HOLD_DURATION
         } else {
             false
         }
     }
+
+    /// Binds `key` to the named action, in addition to the keys already
+    /// bound to it.
+    pub fn map_action(&self, action: &str, key: Key) {
+        let mut inner = self.write_inner();
+        let keys = inner.actions.entry(action.to_owned()).or_default();
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+
+    pub fn unmap_action(&self, action: &str) {
+        self.write_inner().actions.remove(action);
+    }
+
+    pub fn action_keys(&self, action: &str) -> Vec<Key> {
+        self.read_inner()
+            .actions
+            .get(action)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Whether any key bound to the action is pressed.
+    pub fn is_action_pressed(&self, action: &str) -> bool {
+        self.action_keys(action)
+            .into_iter()
+            .any(|key| self.is_pressed(key))
+    }
+
+    /// Whether any key bound to the action is held.
+    pub fn is_action_held(&self, action: &str) -> bool {
+        self.action_keys(action)
+            .into_iter()
+            .any(|key| self.is_held(key))
+    }
 }
 
 impl Default for InputHandler {
@@ -1,29 +1,82 @@
+use std::collections::HashSet;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::MutexGuard;
 use std::time::Duration;
 
+use commands::Commands;
+use events::Events;
+use frame_loop::FrameLoop;
+use frame_loop::FrameLoopConfig;
+use frame_loop::FrameTiming;
 use input_handler::InputHandler;
+use input_handler::Key;
+use plugin::EnginePlugin;
+use plugin::PluginError;
+use renderer::RenderPass;
+use resources::Resource;
+use resources::ResourceMut;
+use resources::ResourceRef;
+use resources::Resources;
+use scene::GameObject;
 use scene::Scene;
+use serialization::ComponentRegistry;
+use state::GameState;
+use state::GameStates;
+use state::InStateSystem;
+use system::Stage;
+use system::System;
+use system::SystemId;
+use system::Systems;
+use time::Subsystem;
+use time::Time;
 use util::internal_mut_struct;
 use util::job::Scheduler;
+use util::logger::LogSeverity;
 use util::logger::LoggerClient;
 use util::thread_category;
 
+pub mod commands;
 pub mod component;
+pub mod component_storage;
+pub mod events;
+pub mod frame_loop;
+pub mod headless;
 pub mod input_handler;
+pub mod plugin;
+pub mod plugins;
+pub mod prefab;
+pub mod query;
 pub mod renderer;
+pub mod resources;
 pub mod scene;
+pub mod serialization;
+pub mod state;
+pub mod system;
+pub mod time;
+pub mod transform;
 
 thread_category!(EngineThreadCategory, Logger, GameObject);
 
+enum SceneChange {
+    Load(Arc<Scene>),
+    Unload(Arc<Scene>),
+    Switch(Arc<Scene>),
+}
+
 struct EngineContextImpl {
-    scene: Arc<Scene>,
+    scenes: Vec<Arc<Scene>>,
+    scene_changes: Vec<SceneChange>,
+    interpolation_alpha: f64,
 }
 
 impl EngineContextImpl {
     fn new(scene: Arc<Scene>) -> Self {
-        Self { scene }
+        Self {
+            scenes: vec![scene],
+            scene_changes: Vec::new(),
+            interpolation_alpha: 0.0,
+        }
     }
 }
 
@@ -32,7 +85,13 @@ internal_mut_struct!(
     EngineContextImpl,
     logger_client: LoggerClient,
     scheduler: Scheduler<EngineThreadCategory>,
-    input_handler: InputHandler
+    input_handler: InputHandler,
+    commands: Commands,
+    events: Events,
+    resources: Resources,
+    states: GameStates,
+    time: Time,
+    persistent_scene: Arc<Scene>
 );
 
 impl EngineContext {
@@ -45,6 +104,12 @@ impl EngineContext {
             logger_client,
             scheduler,
             input_handler: InputHandler::new(),
+            commands: Commands::new(),
+            events: Events::new(),
+            resources: Resources::new(),
+            states: GameStates::new(),
+            time: Time::new(),
+            persistent_scene: Scene::new(),
             inner: Mutex::new(EngineContextImpl::new(scene)),
         }
     }
@@ -61,13 +126,138 @@ impl EngineContext {
         &self.input_handler
     }
 
+    /// Use the command buffer instead of changing the scene directly from
+    /// jobs that run in parallel with others.
+    pub fn commands(&self) -> &Commands {
+        &self.commands
+    }
+
+    pub fn events(&self) -> &Events {
+        &self.events
+    }
+
+    pub fn resources(&self) -> &Resources {
+        &self.resources
+    }
+
+    /// Returns the previous value. See [`Resources`].
+    pub fn insert_resource<T: Resource>(&self, value: T) -> Option<T> {
+        self.resources.insert(value)
+    }
+
+    pub fn resource<T: Resource>(&self) -> Option<ResourceRef<T>> {
+        self.resources.get()
+    }
+
+    pub fn resource_mut<T: Resource>(&self) -> Option<ResourceMut<T>> {
+        self.resources.get_mut()
+    }
+
+    pub fn states(&self) -> &GameStates {
+        &self.states
+    }
+
+    pub fn time(&self) -> &Time {
+        &self.time
+    }
+
+    /// The active scene: the first loaded one, or the persistent scene if
+    /// none is loaded.
     pub fn scene(&self) -> Arc<Scene> {
-        self.lock_inner().scene.clone()
+        self.lock_inner()
+            .scenes
+            .first()
+            .unwrap_or(&self.persistent_scene)
+            .clone()
+    }
+
+    /// The persistent scene followed by the loaded scenes, in loading order.
+    pub fn scenes(&self) -> Vec<Arc<Scene>> {
+        let mut scenes = vec![self.persistent_scene.clone()];
+        scenes.extend(self.lock_inner().scenes.iter().cloned());
+
+        scenes
+    }
+
+    /// A scene that is never unloaded, for game objects that outlive scene
+    /// switches, such as UI or game managers.
+    pub fn persistent_scene(&self) -> &Arc<Scene> {
+        &self.persistent_scene
+    }
+
+    /// Loads `scene` next to the already loaded ones at the end of the
+    /// current update.
+    pub fn load_scene(&self, scene: Arc<Scene>) {
+        self.lock_inner()
+            .scene_changes
+            .push(SceneChange::Load(scene));
+    }
+
+    /// Unloads `scene` at the end of the current update, destroying all of
+    /// its game objects.
+    pub fn unload_scene(&self, scene: &Arc<Scene>) {
+        self.lock_inner()
+            .scene_changes
+            .push(SceneChange::Unload(scene.clone()));
+    }
+
+    /// Unloads every loaded scene and loads `scene` as the active one at the
+    /// end of the current update. The persistent scene is kept.
+    pub fn switch_scene(&self, scene: Arc<Scene>) {
+        self.lock_inner()
+            .scene_changes
+            .push(SceneChange::Switch(scene));
+    }
+
+    /// Applies the pending scene changes in the order they were requested and
+    /// returns the scenes that are no longer loaded.
+    pub(crate) fn apply_scene_changes(&self) -> Vec<Arc<Scene>> {
+        let mut inner = self.lock_inner();
+        let previous = inner.scenes.clone();
+        for change in std::mem::take(&mut inner.scene_changes) {
+            match change {
+                SceneChange::Load(scene) => {
+                    if !inner.scenes.iter().any(|s| Arc::ptr_eq(s, &scene)) {
+                        inner.scenes.push(scene);
+                    }
+                }
+                SceneChange::Unload(scene) => inner.scenes.retain(|s| !Arc::ptr_eq(s, &scene)),
+                SceneChange::Switch(scene) => inner.scenes = vec![scene],
+            }
+        }
+
+        previous
+            .into_iter()
+            .filter(|scene| !inner.scenes.iter().any(|s| Arc::ptr_eq(s, scene)))
+            .collect()
+    }
+
+    /// How far the current frame is between the last fixed step and the next
+    /// one, in `[0, 1)`, for interpolating between the last two simulated
+    /// states when rendering.
+    pub fn interpolation_alpha(&self) -> f64 {
+        self.lock_inner().interpolation_alpha
+    }
+
+    /// Applies the recorded commands to their scenes, logging the ones that
+    /// failed, then delivers the events sent for the next stage. Only
+    /// call it when no jobs are running.
+    pub(crate) fn sync(&self) {
+        for error in self.commands.apply(&self.scene()) {
+            self.logger_client
+                .log(LogSeverity::Warning, format!("Command failed: {error}"));
+        }
+        self.events.deliver(self);
     }
 }
 
 pub struct Engine {
     engine_context: EngineContext,
+    systems: Systems,
+    frame_loop: FrameLoop,
+    component_registry: ComponentRegistry,
+    render_passes: Vec<Arc<dyn RenderPass>>,
+    plugins: Vec<&'static str>,
 }
 
 impl Engine {
@@ -78,24 +268,848 @@ impl Engine {
     ) -> Self {
         Self {
             engine_context: EngineContext::new(logger_client, scheduler, scene),
+            systems: Systems::new(),
+            frame_loop: FrameLoop::new(FrameLoopConfig::default()),
+            component_registry: ComponentRegistry::new(),
+            render_passes: Vec::new(),
+            plugins: Vec::new(),
         }
     }
 
+    /// Like [`new`](Engine::new), then builds `plugins` after their
+    /// dependencies.
+    pub fn with_plugins(
+        scheduler: Scheduler<EngineThreadCategory>,
+        logger_client: LoggerClient,
+        scene: Arc<Scene>,
+        plugins: Vec<Box<dyn EnginePlugin>>,
+    ) -> Result<Self, PluginError> {
+        let plugins = plugin::sort_plugins(plugins)?;
+        let mut engine = Self::new(scheduler, logger_client, scene);
+        for plugin in plugins {
+            plugin.build(&mut engine);
+            engine.plugins.push(plugin.name());
+        }
+
+        Ok(engine)
+    }
+
+    /// The names of the plugins, in the order they were built.
+    pub fn plugins(&self) -> &[&'static str] {
+        &self.plugins
+    }
+
+    pub fn has_plugin(&self, name: &str) -> bool {
+        self.plugins.contains(&name)
+    }
+
+    /// The components saved with scenes, registered by the game and its
+    /// plugins.
+    pub fn component_registry(&self) -> &ComponentRegistry {
+        &self.component_registry
+    }
+
+    pub fn component_registry_mut(&mut self) -> &mut ComponentRegistry {
+        &mut self.component_registry
+    }
+
+    /// Adds a pass run after the built-in drawing, after the passes added
+    /// before it.
+    pub fn add_render_pass<T: RenderPass>(&mut self, pass: T) {
+        self.render_passes.push(Arc::new(pass));
+    }
+
+    /// The passes to give to [`Renderer::render`](renderer::Renderer::render).
+    pub fn render_passes(&self) -> &[Arc<dyn RenderPass>] {
+        &self.render_passes
+    }
+
+    /// Binds `key` to a named action of the [`InputHandler`].
+    pub fn map_action(&mut self, action: &str, key: Key) {
+        self.engine_context.input_handler().map_action(action, key);
+    }
+
     pub fn engine_context(&self) -> &EngineContext {
         &self.engine_context
     }
 
-    pub fn update(&self, delta_time: Duration) {
-        self.engine_context.input_handler().update(delta_time);
-        let scene = self.engine_context.scene();
+    pub fn frame_loop_config(&self) -> FrameLoopConfig {
+        self.frame_loop.config()
+    }
+
+    /// Resets the fixed step accumulator. Panics if the config is invalid;
+    /// see [`FrameLoop::new`].
+    pub fn set_frame_loop_config(&mut self, config: FrameLoopConfig) {
+        self.frame_loop = FrameLoop::new(config);
+    }
+
+    /// Runs one frame over the real time since the previous call, sleeping
+    /// first to honour the target frame rate. This is the main loop entry
+    /// point; see [`FrameLoopConfig`].
+    pub fn tick(&mut self) -> FrameTiming {
+        let elapsed = self.frame_loop.wait();
+        self.step(elapsed)
+    }
+
+    /// Like [`tick`](Engine::tick), over `elapsed` real time instead of the
+    /// measured one and without sleeping. Fixed steps follow the scaled
+    /// time, so slowing time down also slows the simulation down.
+    pub fn step(&mut self, elapsed: Duration) -> FrameTiming {
+        let delta_time = self.begin_frame(elapsed);
+        let timing = self.frame_loop.advance(delta_time);
+
+        let fixed_delta_time = self.frame_loop.config().fixed_delta_time;
+        for _ in 0..timing.fixed_steps {
+            self.fixed_update(fixed_delta_time);
+        }
+        self.engine_context.lock_inner().interpolation_alpha = timing.alpha;
+        self.run_update(elapsed);
+
+        timing
+    }
+
+    /// Adds `system` to [`Stage::Update`].
+    pub fn add_system<S: System>(&mut self, system: S) -> SystemId {
+        self.systems.add(system)
+    }
+
+    /// Each fixed step runs [`Stage::FixedUpdate`] after `on_fixed_update`,
+    /// with [`Time::fixed_delta_time`] set. Each update runs
+    /// [`Stage::PreUpdate`] before `on_update`,
+    /// [`Stage::Update`] after it, [`Stage::PostUpdate`] after
+    /// `on_late_update` and [`Stage::RenderPrep`] once the world transforms
+    /// are up to date. See [`Systems`] for the order within a stage.
+    pub fn add_system_to<S: System>(&mut self, stage: Stage, system: S) -> SystemId {
+        self.systems.add_to(stage, system)
+    }
+
+    /// Like [`add_system_to`](Engine::add_system_to), running `system` only
+    /// while `S` is the current [`GameState`].
+    pub fn add_system_in_state<S: GameState, T: System>(
+        &mut self,
+        stage: Stage,
+        system: T,
+    ) -> SystemId {
+        self.systems.add_to(stage, InStateSystem::<S>::new(system))
+    }
+
+    /// Makes `first` run before `then`. Panics if that contradicts their
+    /// stages or other constraints.
+    pub fn order_systems(&mut self, first: SystemId, then: SystemId) {
+        self.systems.order(first, then);
+    }
+
+    /// Calls `fun` on every item in a parallel job, typically one per game
+    /// object, then applies the commands the jobs recorded.
+    fn for_each_parallel<T, F>(&self, items: &[T], fun: F)
+    where
+        T: Sync,
+        F: Fn(&T) + Sync,
+    {
+        let fun = &fun;
+        let commands = self.engine_context.commands();
+        let first_source = commands.reserve_sources(items.len());
         self.engine_context.scheduler().scoped(|s| {
-            for game_object in scene.game_objects() {
-                if let Some(logic_component) = game_object.logic_component() {
-                    s.schedule_job(EngineThreadCategory::GameObject, move || {
-                        logic_component.run(&self.engine_context);
-                    });
+            for (i, item) in items.iter().enumerate() {
+                s.schedule_job(EngineThreadCategory::GameObject, move || {
+                    commands.with_source(first_source + i as u64, || fun(item));
+                });
+            }
+        });
+        self.engine_context.sync();
+    }
+
+    /// Like [`for_each_parallel`](Engine::for_each_parallel), applying the
+    /// commands recorded for every game object to its own scene.
+    fn for_each_game_object<F>(&self, game_objects: &[Arc<GameObject>], fun: F)
+    where
+        F: Fn(&GameObject) + Sync,
+    {
+        let commands = self.engine_context.commands();
+        self.for_each_parallel(game_objects, |game_object| {
+            commands.with_scene(&game_object.scene(), || fun(game_object));
+        });
+    }
+
+    /// Runs the pending `on_enable`, `on_disable` and `on_start` hooks and
+    /// returns the active game objects of all `scenes`.
+    fn refresh_behaviours(&self, scenes: &[Arc<Scene>]) -> Vec<Arc<GameObject>> {
+        let game_objects: Vec<_> = scenes
+            .iter()
+            .flat_map(|scene| scene.game_object_snapshot())
+            .collect();
+        let commands = self.engine_context.commands();
+        self.for_each_parallel(&game_objects, |(game_object, active)| {
+            commands.with_scene(&game_object.scene(), || {
+                game_object.refresh_behaviours(&self.engine_context, *active);
+            });
+        });
+
+        game_objects
+            .into_iter()
+            .filter(|(_, active)| *active)
+            .map(|(game_object, _)| game_object)
+            .collect()
+    }
+
+    /// Runs one fixed simulation step. [`tick`](Engine::tick) and
+    /// [`step`](Engine::step) call it before [`update`](Engine::update) as
+    /// many times as fixed steps fit into the frame.
+    pub fn fixed_update(&self, fixed_delta_time: Duration) {
+        if self
+            .engine_context
+            .time()
+            .is_subsystem_paused(Subsystem::FixedUpdate)
+        {
+            return;
+        }
+
+        let time = self.engine_context.time();
+        time.set_fixed_delta_time(fixed_delta_time);
+        let scenes = self.engine_context.scenes();
+        let game_objects = self.refresh_behaviours(&scenes);
+        if !time.is_subsystem_paused(Subsystem::Behaviours) {
+            self.for_each_game_object(&game_objects, |game_object| {
+                for behaviour in game_object.running_behaviours() {
+                    behaviour.lock().unwrap().on_fixed_update(
+                        &self.engine_context,
+                        game_object,
+                        fixed_delta_time,
+                    );
                 }
+            });
+        }
+        if !time.is_subsystem_paused(Subsystem::Systems) {
+            self.systems.run(Stage::FixedUpdate, &self.engine_context);
+        }
+    }
+
+    /// Starts a frame of the [`Time`] that lasted `delta_time` in real time,
+    /// updates every loaded scene, then applies the scene changes requested
+    /// during the update. Behaviours get the scaled delta time.
+    pub fn update(&self, delta_time: Duration) {
+        self.begin_frame(delta_time);
+        self.run_update(delta_time);
+    }
+
+    /// Advances the [`Time`], applies the requested [`GameState`]
+    /// transitions and starts a new frame of events, returning the scaled
+    /// delta time.
+    fn begin_frame(&self, delta_time: Duration) -> Duration {
+        let delta_time = self.engine_context.time().advance(delta_time);
+        self.engine_context.states().apply(&self.engine_context);
+        self.engine_context
+            .events()
+            .begin_frame(&self.engine_context);
+
+        delta_time
+    }
+
+    fn run_update(&self, unscaled_delta_time: Duration) {
+        self.engine_context
+            .input_handler()
+            .update(unscaled_delta_time);
+        let time = self.engine_context.time();
+        let delta_time = time.delta_time();
+        let scenes = self.engine_context.scenes();
+
+        let game_objects = self.refresh_behaviours(&scenes);
+        let behaviours_paused = time.is_subsystem_paused(Subsystem::Behaviours);
+        let systems_paused = time.is_subsystem_paused(Subsystem::Systems);
+        let run_stage = |stage| {
+            if !systems_paused {
+                self.systems.run(stage, &self.engine_context);
+            }
+        };
+
+        run_stage(Stage::PreUpdate);
+        if !behaviours_paused {
+            self.for_each_game_object(&game_objects, |game_object| {
+                for behaviour in game_object.running_behaviours() {
+                    behaviour.lock().unwrap().on_update(
+                        &self.engine_context,
+                        game_object,
+                        delta_time,
+                    );
+                }
+            });
+        }
+        run_stage(Stage::Update);
+        if !behaviours_paused {
+            self.for_each_game_object(&game_objects, |game_object| {
+                for behaviour in game_object.running_behaviours() {
+                    behaviour.lock().unwrap().on_late_update(
+                        &self.engine_context,
+                        game_object,
+                        delta_time,
+                    );
+                }
+            });
+        }
+        run_stage(Stage::PostUpdate);
+        for scene in &scenes {
+            scene.update_transforms();
+        }
+        run_stage(Stage::RenderPrep);
+        for scene in &scenes {
+            self.remove_destroyed(scene);
+        }
+
+        for scene in self.engine_context.apply_scene_changes() {
+            for root in scene.roots() {
+                scene.destroy(root).unwrap();
+            }
+            self.remove_destroyed(&scene);
+        }
+    }
+
+    /// Calls the destroy hooks of the game objects marked for destruction in
+    /// `scene`, then removes them.
+    fn remove_destroyed(&self, scene: &Arc<Scene>) {
+        // on_destroy may destroy further game objects, which get their hooks
+        // called in the same frame.
+        let mut notified = HashSet::new();
+        loop {
+            let destroyed: Vec<_> = scene
+                .destroyed_game_objects()
+                .into_iter()
+                .filter(|game_object| notified.insert(game_object.id()))
+                .collect();
+            if destroyed.is_empty() {
+                break;
             }
+            self.engine_context.commands().with_scene(scene, || {
+                for game_object in destroyed {
+                    game_object.destroy_behaviours(&self.engine_context);
+                }
+            });
+            self.engine_context.sync();
+        }
+        scene.remove_destroyed();
+        self.engine_context.events().forget_destroyed();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use util::job::Scheduler;
+    use util::logger::create_logger;
+    use util::thread_pool_descriptor;
+
+    use super::Engine;
+    use super::EngineThreadCategory;
+    use crate::component::Behaviour;
+    use crate::component::LogicComponent;
+    use crate::events::Delivery;
+    use crate::frame_loop::FrameLoopConfig;
+    use crate::input_handler::Key;
+    use crate::plugin::EnginePlugin;
+    use crate::renderer::RenderPass;
+    use crate::scene::GameObject;
+    use crate::scene::Scene;
+    use crate::state::GameState;
+    use crate::state::InState;
+    use crate::system::FnSystem;
+    use crate::system::QuerySystem;
+    use crate::system::Stage;
+    use crate::time::Subsystem;
+    use crate::EngineContext;
+
+    thread_pool_descriptor!(EngineThreadCategory, Logger: 1, GameObject: 2);
+
+    fn test_engine(scene: Arc<Scene>) -> Engine {
+        let (_, logger_client) = create_logger(1, Box::new(io::sink()));
+        Engine::new(
+            Scheduler::new(ThreadPoolDescriptor {}),
+            logger_client,
+            scene,
+        )
+    }
+
+    struct Recorder(Arc<Mutex<Vec<&'static str>>>);
+
+    impl Behaviour for Recorder {
+        fn on_start(&mut self, _: &EngineContext, _: &GameObject) {
+            self.0.lock().unwrap().push("start");
+        }
+
+        fn on_enable(&mut self, _: &EngineContext, _: &GameObject) {
+            self.0.lock().unwrap().push("enable");
+        }
+
+        fn on_disable(&mut self, _: &EngineContext, _: &GameObject) {
+            self.0.lock().unwrap().push("disable");
+        }
+
+        fn on_fixed_update(&mut self, _: &EngineContext, _: &GameObject, _: Duration) {
+            self.0.lock().unwrap().push("fixed_update");
+        }
+
+        fn on_update(&mut self, _: &EngineContext, _: &GameObject, _: Duration) {
+            self.0.lock().unwrap().push("update");
+        }
+
+        fn on_late_update(&mut self, _: &EngineContext, _: &GameObject, _: Duration) {
+            self.0.lock().unwrap().push("late_update");
+        }
+
+        fn on_destroy(&mut self, _: &EngineContext, _: &GameObject) {
+            self.0.lock().unwrap().push("destroy");
+        }
+    }
+
+    #[test]
+    fn test_behaviour_lifecycle() {
+        let scene = Scene::new();
+        let engine = test_engine(scene.clone());
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let game_object = scene.game_object(scene.add_game_object()).unwrap();
+        game_object.add_behaviour(Recorder(events.clone()));
+
+        let delta_time = Duration::from_millis(16);
+        engine.fixed_update(delta_time);
+        engine.update(delta_time);
+        game_object.set_enabled(false);
+        engine.update(delta_time);
+        game_object.set_enabled(true);
+        game_object.destroy();
+        engine.update(delta_time);
+
+        assert_eq!(
+            vec![
+                "enable",
+                "start",
+                "fixed_update",
+                "update",
+                "late_update",
+                "disable",
+                "enable",
+                "update",
+                "late_update",
+                "disable",
+                "destroy"
+            ],
+            *events.lock().unwrap()
+        );
+        assert!(scene.game_objects().is_empty());
+    }
+
+    #[derive(Default)]
+    struct Counter {
+        count: u32,
+    }
+
+    impl Behaviour for Counter {
+        fn on_update(&mut self, _: &EngineContext, game_object: &GameObject, _: Duration) {
+            self.count += 1;
+            assert!(game_object.with_behaviour(|_: &mut Counter| ()).is_none());
+        }
+    }
+
+    #[derive(Default)]
+    struct Observer {
+        observed: Vec<u32>,
+    }
+
+    impl Behaviour for Observer {
+        fn on_late_update(&mut self, _: &EngineContext, game_object: &GameObject, _: Duration) {
+            let count = game_object.with_behaviour(|counter: &mut Counter| counter.count);
+            self.observed.extend(count);
+        }
+    }
+
+    #[test]
+    fn test_behaviour_state() {
+        let scene = Scene::new();
+        let engine = test_engine(scene.clone());
+        let game_object = scene.game_object(scene.add_game_object()).unwrap();
+        game_object.add_behaviour(Counter::default());
+        game_object.add_behaviour(Observer::default());
+
+        let mut frames = 0u32;
+        game_object.add_logic_component(move |_, game_object, _| {
+            frames += 1;
+            game_object.add_component(frames);
+        });
+
+        for _ in 0..3 {
+            engine.update(Duration::from_millis(16));
+        }
+        assert_eq!(
+            Some(vec![1, 2, 3]),
+            game_object.with_behaviour(|observer: &mut Observer| observer.observed.clone())
+        );
+        assert_eq!(3, *game_object.get_component::<u32>().unwrap());
+    }
+
+    #[test]
+    fn test_scene_switching() {
+        let level = Scene::new();
+        let engine = test_engine(level.clone());
+        let context = engine.engine_context();
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        level
+            .game_object(level.add_game_object())
+            .unwrap()
+            .add_behaviour(Recorder(events.clone()));
+        let ui = context.persistent_scene();
+        let counter = ui.game_object(ui.add_game_object()).unwrap();
+        counter.add_behaviour(Counter::default());
+        engine.update(Duration::from_millis(16));
+
+        let next_level = Scene::new();
+        context.switch_scene(next_level.clone());
+        assert!(Arc::ptr_eq(&level, &context.scene()));
+        engine.update(Duration::from_millis(16));
+        assert!(Arc::ptr_eq(&next_level, &context.scene()));
+        assert!(level.game_objects().is_empty());
+        assert_eq!(
+            vec![
+                "enable",
+                "start",
+                "update",
+                "late_update",
+                "update",
+                "late_update",
+                "disable",
+                "destroy"
+            ],
+            *events.lock().unwrap()
+        );
+
+        let overlay = Scene::new();
+        overlay
+            .game_object(overlay.add_game_object())
+            .unwrap()
+            .add_behaviour(Recorder(events.clone()));
+        context.load_scene(overlay.clone());
+        context.load_scene(overlay.clone());
+        engine.update(Duration::from_millis(16));
+        engine.update(Duration::from_millis(16));
+        assert_eq!(3, context.scenes().len());
+        assert!(Arc::ptr_eq(&next_level, &context.scene()));
+        assert_eq!(
+            Some(4),
+            counter.with_behaviour(|counter: &mut Counter| counter.count)
+        );
+
+        context.unload_scene(&next_level);
+        context.unload_scene(&overlay);
+        engine.update(Duration::from_millis(16));
+        assert!(Arc::ptr_eq(ui, &context.scene()));
+        assert_eq!(1, context.scenes().len());
+    }
+
+    struct Doomed;
+
+    #[test]
+    fn test_commands_of_other_scenes() {
+        let level = Scene::new();
+        let mut engine = test_engine(level.clone());
+        let context = engine.engine_context();
+        let level_object = level.add_game_object();
+
+        // Game object ids are per scene, so these collide with the level.
+        let ui = context.persistent_scene().clone();
+        let ui_object = ui.add_game_object();
+        assert_eq!(level_object, ui_object);
+        ui.game_object(ui_object)
+            .unwrap()
+            .add_logic_component(|engine_context, game_object, _| {
+                engine_context.commands().despawn(game_object.id());
+            });
+        let overlay = Scene::new();
+        let overlay_object = overlay.add_game_object();
+        assert_eq!(level_object, overlay_object);
+        overlay.add_component(overlay_object, Doomed).unwrap();
+        context.load_scene(overlay.clone());
+        engine.update(Duration::from_millis(16));
+
+        engine.add_system(QuerySystem::<(&Doomed,), _>::new(
+            |engine_context, scene, id, _| {
+                assert!(scene.get_component::<Doomed>(id).is_some());
+                engine_context.commands().despawn(id);
+            },
+        ));
+        engine.update(Duration::from_millis(16));
+        assert_eq!(vec![level_object], level.game_objects());
+        assert!(ui.game_objects().is_empty());
+        assert!(overlay.game_objects().is_empty());
+    }
+
+    #[test]
+    fn test_fixed_timestep() {
+        let scene = Scene::new();
+        let mut engine = test_engine(scene.clone());
+        engine.set_frame_loop_config(FrameLoopConfig {
+            fixed_delta_time: Duration::from_millis(20),
+            max_fixed_steps: 5,
+            target_frame_rate: None,
+        });
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        scene
+            .game_object(scene.add_game_object())
+            .unwrap()
+            .add_behaviour(Recorder(events.clone()));
+
+        let timing = engine.step(Duration::from_millis(50));
+        assert_eq!(2, timing.fixed_steps);
+        assert_eq!(0.5, engine.engine_context().interpolation_alpha());
+        engine.step(Duration::from_millis(5));
+        assert_eq!(
+            vec![
+                "enable",
+                "start",
+                "fixed_update",
+                "fixed_update",
+                "update",
+                "late_update",
+                "update",
+                "late_update"
+            ],
+            *events.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_time_scale_and_pause() {
+        let scene = Scene::new();
+        let mut engine = test_engine(scene.clone());
+        engine.set_frame_loop_config(FrameLoopConfig {
+            fixed_delta_time: Duration::from_millis(20),
+            max_fixed_steps: 5,
+            target_frame_rate: None,
+        });
+        let counter = scene.game_object(scene.add_game_object()).unwrap();
+        counter.add_behaviour(Counter::default());
+
+        engine.engine_context().time().set_time_scale(0.5);
+        assert_eq!(1, engine.step(Duration::from_millis(40)).fixed_steps);
+        assert_eq!(
+            Duration::from_millis(20),
+            engine.engine_context().time().elapsed()
+        );
+
+        engine.engine_context().time().set_paused(true);
+        assert_eq!(0, engine.step(Duration::from_millis(40)).fixed_steps);
+        engine
+            .engine_context()
+            .time()
+            .set_subsystem_paused(Subsystem::Behaviours, true);
+        engine.step(Duration::from_millis(40));
+        assert_eq!(3, engine.engine_context().time().frame_count());
+        assert_eq!(
+            Duration::from_millis(120),
+            engine.engine_context().time().unscaled_elapsed()
+        );
+        assert_eq!(
+            Some(2),
+            counter.with_behaviour(|counter: &mut Counter| counter.count)
+        );
+    }
+
+    #[test]
+    fn test_system_stages() {
+        let mut engine = test_engine(Scene::new());
+        let stages = Arc::new(Mutex::new(Vec::new()));
+        let update_stages = [
+            Stage::PreUpdate,
+            Stage::Update,
+            Stage::PostUpdate,
+            Stage::RenderPrep,
+        ];
+        for stage in update_stages.into_iter().rev() {
+            let stages = stages.clone();
+            engine.add_system_to(
+                stage,
+                FnSystem::new(Vec::new(), move |_| stages.lock().unwrap().push(stage)),
+            );
+        }
+
+        engine.update(Duration::from_millis(16));
+        assert_eq!(update_stages.to_vec(), *stages.lock().unwrap());
+    }
+
+    struct Damage(u32);
+
+    struct Score;
+
+    #[test]
+    fn test_events() {
+        let scene = Scene::new();
+        let engine = test_engine(scene.clone());
+        let events = engine.engine_context().events();
+        let target = scene.game_object(scene.add_game_object()).unwrap();
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let received_clone = received.clone();
+        events.subscribe_to(&target, move |_, _, damage: &Damage| {
+            received_clone.lock().unwrap().push(damage.0);
+        });
+        let scores = Arc::new(Mutex::new(0));
+        let scores_clone = scores.clone();
+        events.subscribe(move |_, _, _: &Score| *scores_clone.lock().unwrap() += 1);
+
+        let attacker = scene.game_object(scene.add_game_object()).unwrap();
+        let attacked = target.clone();
+        attacker.add_logic_component(move |engine_context, _, _| {
+            engine_context
+                .events()
+                .send_to(Delivery::NextStage, &attacked, Damage(3));
         });
+        events.send(Delivery::NextStage, Damage(1));
+        events.send(Delivery::NextFrame, Score);
+
+        engine.update(Duration::from_millis(16));
+        assert_eq!(vec![3], *received.lock().unwrap());
+        assert_eq!(1, *scores.lock().unwrap());
+        let mut read = Vec::new();
+        events.read(|target, damage: &Damage| read.push((target.is_some(), damage.0)));
+        assert!(read.is_empty());
+
+        engine.update(Duration::from_millis(16));
+        events.read(|target, damage: &Damage| read.push((target.is_some(), damage.0)));
+        assert_eq!(vec![(false, 1), (true, 3)], read);
+
+        target.destroy();
+        engine.update(Duration::from_millis(16));
+        engine.update(Duration::from_millis(16));
+        assert_eq!(vec![3, 3, 3], *received.lock().unwrap());
+    }
+
+    struct StateRecorder(&'static str, Arc<Mutex<Vec<String>>>);
+
+    impl StateRecorder {
+        fn record(&self, hook: &str) {
+            self.1.lock().unwrap().push(format!("{} {hook}", self.0));
+        }
+    }
+
+    macro_rules! recorded_state {
+        ($name:ident) => {
+            struct $name(StateRecorder);
+
+            impl GameState for $name {
+                fn on_enter(&mut self, _: &EngineContext) {
+                    self.0.record("enter");
+                }
+
+                fn on_exit(&mut self, _: &EngineContext) {
+                    self.0.record("exit");
+                }
+
+                fn on_pause(&mut self, _: &EngineContext) {
+                    self.0.record("pause");
+                }
+
+                fn on_resume(&mut self, _: &EngineContext) {
+                    self.0.record("resume");
+                }
+            }
+        };
+    }
+
+    recorded_state!(MainMenu);
+    recorded_state!(Playing);
+    recorded_state!(Paused);
+
+    #[test]
+    fn test_game_states() {
+        let scene = Scene::new();
+        let mut engine = test_engine(scene.clone());
+        let hooks = Arc::new(Mutex::new(Vec::new()));
+        let recorder = |name| StateRecorder(name, hooks.clone());
+
+        let system_runs = Arc::new(Mutex::new(0));
+        let system_runs_clone = system_runs.clone();
+        engine.add_system_in_state::<Playing, _>(
+            Stage::Update,
+            FnSystem::new(Vec::new(), move |_| *system_runs_clone.lock().unwrap() += 1),
+        );
+        let logic_runs = Arc::new(Mutex::new(0));
+        let logic_runs_clone = logic_runs.clone();
+        let game_object = scene.game_object(scene.add_game_object()).unwrap();
+        game_object.add_behaviour(InState::<Playing, _>::new(LogicComponent::new(
+            move |_, _, _| *logic_runs_clone.lock().unwrap() += 1,
+        )));
+
+        let states = engine.engine_context().states();
+        states.push(MainMenu(recorder("menu")));
+        engine.update(Duration::from_millis(16));
+        assert!(states.is_current::<MainMenu>());
+        states.replace(Playing(recorder("playing")));
+        engine.update(Duration::from_millis(16));
+        engine.update(Duration::from_millis(16));
+        states.push(Paused(recorder("paused")));
+        engine.update(Duration::from_millis(16));
+        assert!(states.contains::<Playing>());
+        assert!(!states.is_current::<Playing>());
+        assert_eq!(2, states.depth());
+        states.pop();
+        engine.update(Duration::from_millis(16));
+
+        assert_eq!(
+            vec![
+                "menu enter",
+                "menu exit",
+                "playing enter",
+                "playing pause",
+                "paused enter",
+                "paused exit",
+                "playing resume",
+            ],
+            *hooks.lock().unwrap()
+        );
+        assert!(states.is_current::<Playing>());
+        assert_eq!(3, *system_runs.lock().unwrap());
+        assert_eq!(3, *logic_runs.lock().unwrap());
+    }
+
+    struct Overlay;
+
+    impl RenderPass for Overlay {
+        fn render(&self, _: &[Arc<Scene>]) {}
+    }
+
+    struct ControlsPlugin;
+
+    impl EnginePlugin for ControlsPlugin {
+        fn name(&self) -> &'static str {
+            "controls"
+        }
+
+        fn build(&self, engine: &mut Engine) {
+            engine.map_action("jump", Key::Space);
+            engine.map_action("jump", Key::W);
+            engine.add_render_pass(Overlay);
+        }
+    }
+
+    #[test]
+    fn test_plugin_registration() {
+        let (_, logger_client) = create_logger(1, Box::new(io::sink()));
+        let engine = Engine::with_plugins(
+            Scheduler::new(ThreadPoolDescriptor {}),
+            logger_client,
+            Scene::new(),
+            vec![Box::new(ControlsPlugin)],
+        )
+        .unwrap();
+        assert_eq!(1, engine.render_passes().len());
+
+        let input_handler = engine.engine_context().input_handler();
+        assert_eq!(vec![Key::Space, Key::W], input_handler.action_keys("jump"));
+        assert!(!input_handler.is_action_pressed("jump"));
+        input_handler.key_state_changed(Key::W, true);
+        assert!(input_handler.is_action_pressed("jump"));
+        assert!(!input_handler.is_action_pressed("fire"));
     }
 }
@@ -0,0 +1,160 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+use crate::Engine;
+
+/// An engine extension, set up once when the engine is created with
+/// [`Engine::with_plugins`]. Plugins register whatever they need through the
+/// engine: systems, resources, event subscriptions, serializable components
+/// in [`Engine::component_registry_mut`], render passes, input actions, or
+/// game objects in the persistent scene.
+pub trait EnginePlugin: 'static {
+    /// Unique among the plugins of an engine.
+    fn name(&self) -> &'static str;
+
+    /// The names of the plugins that must be built before this one.
+    fn dependencies(&self) -> Vec<&'static str> {
+        Vec::new()
+    }
+
+    fn build(&self, engine: &mut Engine);
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum PluginError {
+    Duplicate(&'static str),
+    MissingDependency {
+        plugin: &'static str,
+        dependency: &'static str,
+    },
+    /// The plugins depending on each other, directly or not.
+    CyclicDependencies(Vec<&'static str>),
+}
+
+impl Display for PluginError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            PluginError::Duplicate(name) => write!(f, "plugin `{name}` is added twice"),
+            PluginError::MissingDependency { plugin, dependency } => write!(
+                f,
+                "plugin `{plugin}` depends on `{dependency}`, which is not added"
+            ),
+            PluginError::CyclicDependencies(names) => write!(
+                f,
+                "plugins {} depend on each other",
+                names
+                    .iter()
+                    .map(|name| format!("`{name}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+impl Error for PluginError {}
+
+/// Orders `plugins` so that every plugin comes after its dependencies,
+/// keeping the given order where possible.
+pub(crate) fn sort_plugins(
+    plugins: Vec<Box<dyn EnginePlugin>>,
+) -> Result<Vec<Box<dyn EnginePlugin>>, PluginError> {
+    let names: Vec<_> = plugins.iter().map(|plugin| plugin.name()).collect();
+    for (i, name) in names.iter().enumerate() {
+        if names[..i].contains(name) {
+            return Err(PluginError::Duplicate(name));
+        }
+    }
+    for plugin in &plugins {
+        if let Some(dependency) = plugin
+            .dependencies()
+            .into_iter()
+            .find(|dependency| !names.contains(dependency))
+        {
+            return Err(PluginError::MissingDependency {
+                plugin: plugin.name(),
+                dependency,
+            });
+        }
+    }
+
+    let mut pending = plugins;
+    let mut sorted: Vec<Box<dyn EnginePlugin>> = Vec::new();
+    while !pending.is_empty() {
+        let ready = pending.iter().position(|plugin| {
+            plugin
+                .dependencies()
+                .iter()
+                .all(|dependency| sorted.iter().any(|p| p.name() == *dependency))
+        });
+        match ready {
+            Some(ready) => sorted.push(pending.remove(ready)),
+            None => {
+                return Err(PluginError::CyclicDependencies(
+                    pending.iter().map(|plugin| plugin.name()).collect(),
+                ))
+            }
+        }
+    }
+
+    Ok(sorted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sort_plugins;
+    use super::EnginePlugin;
+    use super::PluginError;
+    use crate::Engine;
+
+    struct TestPlugin(&'static str, Vec<&'static str>);
+
+    impl EnginePlugin for TestPlugin {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn dependencies(&self) -> Vec<&'static str> {
+            self.1.clone()
+        }
+
+        fn build(&self, _: &mut Engine) {}
+    }
+
+    fn names(plugins: Vec<TestPlugin>) -> Result<Vec<&'static str>, PluginError> {
+        let plugins = plugins
+            .into_iter()
+            .map(|plugin| Box::new(plugin) as Box<dyn EnginePlugin>)
+            .collect();
+        sort_plugins(plugins).map(|plugins| plugins.iter().map(|p| p.name()).collect())
+    }
+
+    #[test]
+    fn test_plugin_order() {
+        assert_eq!(
+            Ok(vec!["audio", "physics", "ui", "game"]),
+            names(vec![
+                TestPlugin("game", vec!["physics", "ui"]),
+                TestPlugin("audio", vec![]),
+                TestPlugin("physics", vec![]),
+                TestPlugin("ui", vec!["audio"]),
+            ])
+        );
+        assert_eq!(
+            Err(PluginError::Duplicate("ui")),
+            names(vec![TestPlugin("ui", vec![]), TestPlugin("ui", vec![])])
+        );
+        assert_eq!(
+            Err(PluginError::MissingDependency {
+                plugin: "ui",
+                dependency: "audio"
+            }),
+            names(vec![TestPlugin("ui", vec!["audio"])])
+        );
+        assert_eq!(
+            Err(PluginError::CyclicDependencies(vec!["a", "b"])),
+            names(vec![TestPlugin("a", vec!["b"]), TestPlugin("b", vec!["a"])])
+        );
+    }
+}
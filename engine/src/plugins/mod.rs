@@ -0,0 +1,4 @@
+// Each plugin is behind the feature of its name, and is always built for
+// tests so that a plain `cargo test` covers it.
+#[cfg(any(feature = "physics", test))]
+pub mod physics;
@@ -0,0 +1,81 @@
+use util::math::vector::Vector3f;
+
+use crate::plugin::EnginePlugin;
+use crate::system::QuerySystem;
+use crate::system::Stage;
+use crate::transform::Transform;
+use crate::Engine;
+
+/// Units per second, in the space of the parent game object.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct Velocity(pub Vector3f);
+
+/// Moves every game object with a [`Velocity`] on each fixed step.
+pub struct PhysicsPlugin;
+
+impl EnginePlugin for PhysicsPlugin {
+    fn name(&self) -> &'static str {
+        "physics"
+    }
+
+    fn build(&self, engine: &mut Engine) {
+        engine.add_system_to(
+            Stage::FixedUpdate,
+            QuerySystem::<(&mut Transform, &Velocity), _>::new(
+                |engine_context, _, _, (transform, velocity)| {
+                    let dt = engine_context.time().fixed_delta_time().as_secs_f64();
+                    transform.translate(&(velocity.0 * dt));
+                },
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::time::Duration;
+
+    use util::job::Scheduler;
+    use util::logger::create_logger;
+    use util::math::vector::Vector3f;
+    use util::thread_pool_descriptor;
+
+    use super::PhysicsPlugin;
+    use super::Velocity;
+    use crate::frame_loop::FrameLoopConfig;
+    use crate::scene::Scene;
+    use crate::transform::Transform;
+    use crate::Engine;
+    use crate::EngineThreadCategory;
+
+    thread_pool_descriptor!(EngineThreadCategory, Logger: 1, GameObject: 2);
+
+    #[test]
+    fn test_physics_plugin() {
+        let scene = Scene::new();
+        let (_, logger_client) = create_logger(1, Box::new(io::sink()));
+        let mut engine = Engine::with_plugins(
+            Scheduler::new(ThreadPoolDescriptor {}),
+            logger_client,
+            scene.clone(),
+            vec![Box::new(PhysicsPlugin)],
+        )
+        .unwrap();
+        assert!(engine.has_plugin("physics"));
+        engine.set_frame_loop_config(FrameLoopConfig {
+            fixed_delta_time: Duration::from_millis(250),
+            ..FrameLoopConfig::default()
+        });
+
+        let id = scene.add_game_object();
+        scene
+            .add_component(id, Velocity(Vector3f::new(2.0, 0.0, 0.0)))
+            .unwrap();
+        engine.step(Duration::from_millis(600));
+        assert_eq!(
+            Vector3f::new(1.0, 0.0, 0.0),
+            scene.get_component::<Transform>(id).unwrap().position()
+        );
+    }
+}
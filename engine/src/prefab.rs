@@ -0,0 +1,773 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::sync::RwLock;
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::scene::GameObjectId;
+use crate::scene::Scene;
+use crate::serialization::ComponentRegistry;
+use crate::serialization::GameObjectTemplate;
+use crate::serialization::LoadedGameObject;
+use crate::serialization::PreparedComponent;
+use crate::serialization::SerializationError;
+
+/// Per-instance changes to the component values of a prefab. Game objects
+/// are addressed by their index in the prefab in pre-order, 0 being the root,
+/// and fields by a dot-separated path into the component, e.g.
+/// `stats.max_health` or `position.1`. An empty path replaces the whole
+/// component, or adds it if the prefab doesn't have it.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(from = "Vec<OverrideEntry>", into = "Vec<OverrideEntry>")]
+pub struct PrefabOverrides {
+    entries: BTreeMap<(usize, String, String), Value>,
+}
+
+/// How an override is saved.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Override", deny_unknown_fields)]
+struct OverrideEntry {
+    node: usize,
+    component: String,
+    field: String,
+    value: Value,
+}
+
+impl From<Vec<OverrideEntry>> for PrefabOverrides {
+    fn from(entries: Vec<OverrideEntry>) -> Self {
+        Self {
+            entries: entries
+                .into_iter()
+                .map(|entry| ((entry.node, entry.component, entry.field), entry.value))
+                .collect(),
+        }
+    }
+}
+
+impl From<PrefabOverrides> for Vec<OverrideEntry> {
+    fn from(overrides: PrefabOverrides) -> Self {
+        overrides
+            .entries
+            .into_iter()
+            .map(|((node, component, field), value)| OverrideEntry {
+                node,
+                component,
+                field,
+                value,
+            })
+            .collect()
+    }
+}
+
+impl PrefabOverrides {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Panics if `value` can't be represented as JSON, e.g. a map with
+    /// non-string keys.
+    pub fn set<T: Serialize>(
+        &mut self,
+        node: usize,
+        component: &str,
+        field: &str,
+        value: T,
+    ) -> &mut Self {
+        let value = serde_json::to_value(value).expect("override value is not serializable");
+        self.entries
+            .insert((node, component.to_owned(), field.to_owned()), value);
+        self
+    }
+
+    pub fn remove(&mut self, node: usize, component: &str, field: &str) -> &mut Self {
+        self.entries
+            .remove(&(node, component.to_owned(), field.to_owned()));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn apply(
+        &self,
+        node: usize,
+        components: &mut BTreeMap<String, Value>,
+    ) -> Result<(), SerializationError> {
+        for ((_, component, field), value) in self
+            .entries
+            .range((node, String::new(), String::new())..(node + 1, String::new(), String::new()))
+        {
+            let target = components.entry(component.clone()).or_insert(Value::Null);
+            *field_mut(component, target, field)? = value.clone();
+        }
+
+        Ok(())
+    }
+
+    /// The overridden fields of one component, with their values.
+    fn component_entries<'a>(
+        &'a self,
+        node: usize,
+        component: &'a str,
+    ) -> impl Iterator<Item = (&'a String, &'a Value)> + 'a {
+        self.entries
+            .range((node, component.to_owned(), String::new())..)
+            .take_while(move |((n, c, _), _)| *n == node && c == component)
+            .map(|((_, _, field), value)| (field, value))
+    }
+
+    /// Like `apply` for one component.
+    fn apply_component(
+        &self,
+        node: usize,
+        component: &str,
+        target: &mut Value,
+    ) -> Result<(), SerializationError> {
+        for (field, value) in self.component_entries(node, component) {
+            *field_mut(component, target, field)? = value.clone();
+        }
+
+        Ok(())
+    }
+
+    /// Whether `field` is overridden, as a whole or as part of an overridden
+    /// parent.
+    fn covers(&self, node: usize, component: &str, field: &str) -> bool {
+        self.component_entries(node, component)
+            .any(|(overridden, _)| {
+                overridden.is_empty()
+                    || field == overridden
+                    || field.starts_with(&format!("{overridden}."))
+            })
+    }
+
+    /// The fields overridden differently in `self` and `other`.
+    fn differences(&self, other: &PrefabOverrides) -> BTreeSet<FieldPath> {
+        self.entries
+            .keys()
+            .chain(other.entries.keys())
+            .filter(|key| self.entries.get(*key) != other.entries.get(*key))
+            .cloned()
+            .collect()
+    }
+}
+
+/// (node, component, field)
+type FieldPath = (usize, String, String);
+
+/// The value at the dot-separated path `field` of `target`, a value of
+/// `component`.
+fn field_mut<'a>(
+    component: &str,
+    target: &'a mut Value,
+    field: &str,
+) -> Result<&'a mut Value, SerializationError> {
+    let invalid = |message| SerializationError::InvalidComponent {
+        name: component.to_owned(),
+        message,
+    };
+    let mut target = target;
+    if !field.is_empty() {
+        for key in field.split('.') {
+            target = match target {
+                Value::Array(items) => key
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| items.get_mut(index))
+                    .ok_or_else(|| invalid(format!("no element `{key}` in `{field}`")))?,
+                Value::Object(fields) => fields
+                    .get_mut(key)
+                    .ok_or_else(|| invalid(format!("no field `{key}` in `{field}`")))?,
+                _ => return Err(invalid(format!("`{key}` in `{field}` is not a field"))),
+            };
+        }
+    }
+
+    Ok(target)
+}
+
+struct PrefabData {
+    template: GameObjectTemplate,
+    /// The fields changed by `set_field`, oldest first.
+    changes: Vec<FieldPath>,
+}
+
+/// A reusable game object subtree. Every instance is linked to its prefab
+/// through the [`PrefabInstance`] component on its root, so changes to the
+/// prefab can be pushed to the instances with [`sync`](Prefab::sync), while
+/// their overrides are kept.
+pub struct Prefab {
+    registry: ComponentRegistry,
+    data: RwLock<PrefabData>,
+}
+
+impl Prefab {
+    /// Parses a prefab written like a single game object of a saved scene,
+    /// children included.
+    pub fn load(registry: &ComponentRegistry, text: &str) -> Result<Arc<Self>, SerializationError> {
+        let game_object: LoadedGameObject = registry.parse(text)?;
+
+        Ok(Arc::new(Self {
+            registry: registry.clone(),
+            data: RwLock::new(PrefabData {
+                template: game_object.into(),
+                changes: Vec::new(),
+            }),
+        }))
+    }
+
+    /// Captures the game object `root` of `scene` and its descendants.
+    pub fn from_game_object(
+        registry: &ComponentRegistry,
+        scene: &Scene,
+        root: GameObjectId,
+    ) -> Result<Arc<Self>, SerializationError> {
+        Self::load(registry, &registry.save_game_object(scene, root)?)
+    }
+
+    /// Adds a copy of the prefab to `scene`, returning the id of its root.
+    /// Nothing is added if an override doesn't fit the prefab.
+    pub fn instantiate(
+        self: &Arc<Self>,
+        scene: &Scene,
+        overrides: PrefabOverrides,
+    ) -> Result<GameObjectId, SerializationError> {
+        let data = self.data.read().unwrap();
+        let root =
+            data.template
+                .build_with(&self.registry, scene, None, &mut |node, components| {
+                    overrides.apply(node, components)
+                })?;
+
+        scene.add_component(
+            root,
+            PrefabInstance {
+                prefab: self.clone(),
+                game_objects: scene
+                    .depth_first(Some(root))
+                    .into_iter()
+                    .map(Some)
+                    .collect(),
+                overrides,
+                synced_changes: data.changes.len(),
+            },
+        )?;
+
+        Ok(root)
+    }
+
+    /// Changes the default value of a field of one game object of the
+    /// prefab; see [`PrefabOverrides`] for the addressing. Call
+    /// [`sync`](Prefab::sync) to update existing instances.
+    pub fn set_field<T: Serialize>(
+        &self,
+        node: usize,
+        component: &str,
+        field: &str,
+        value: T,
+    ) -> Result<(), SerializationError> {
+        let mut overrides = PrefabOverrides::new();
+        overrides.set(node, component, field, value);
+
+        let mut data = self.data.write().unwrap();
+        let template_node =
+            data.template
+                .node_mut(node)
+                .ok_or_else(|| SerializationError::InvalidComponent {
+                    name: component.to_owned(),
+                    message: format!("the prefab has no game object {node}"),
+                })?;
+        let mut components = template_node.components.clone();
+        overrides.apply(0, &mut components)?;
+        // Fails if the new value doesn't fit the component.
+        if let Some(value) = components.get(component) {
+            self.registry.prepare(component, value.clone()).map(drop)?;
+        }
+        template_node.components = components;
+        data.changes
+            .push((node, component.to_owned(), field.to_owned()));
+
+        Ok(())
+    }
+
+    /// Writes the fields changed with [`set_field`](Prefab::set_field) since
+    /// the last sync to all instances of the prefab in `scene`, except the
+    /// fields they override. Everything else keeps its runtime value.
+    pub fn sync(self: &Arc<Self>, scene: &Scene) -> Result<(), SerializationError> {
+        let mut instances = Vec::new();
+        scene
+            .query::<(&PrefabInstance,)>()
+            .for_each(|root, (instance,)| {
+                if Arc::ptr_eq(&instance.prefab, self) {
+                    instances.push(root);
+                }
+            });
+
+        for root in instances {
+            self.sync_instance(scene, root)?;
+        }
+
+        Ok(())
+    }
+
+    /// Changes the overrides of the instance rooted at `root` and writes the
+    /// fields whose overrides changed.
+    pub fn override_instance<F: FnOnce(&mut PrefabOverrides)>(
+        self: &Arc<Self>,
+        scene: &Scene,
+        root: GameObjectId,
+        fun: F,
+    ) -> Result<(), SerializationError> {
+        let mut instance = scene
+            .get_component::<PrefabInstance>(root)
+            .filter(|instance| Arc::ptr_eq(&instance.prefab, self))
+            .map(|instance| instance.clone())
+            .ok_or_else(|| SerializationError::InvalidComponent {
+                name: "PrefabInstance".to_owned(),
+                message: format!("{root} is not an instance of this prefab"),
+            })?;
+        let previous = instance.overrides.clone();
+        fun(&mut instance.overrides);
+        let changed: Vec<FieldPath> = previous
+            .differences(&instance.overrides)
+            .into_iter()
+            .collect();
+
+        let data = self.data.read().unwrap();
+        self.write_fields(scene, &data.template, &instance, &changed)?;
+        // Only once the new overrides proved valid.
+        if let Some(mut stored) = scene.get_component_mut::<PrefabInstance>(root) {
+            stored.overrides = instance.overrides;
+        }
+
+        Ok(())
+    }
+
+    fn sync_instance(&self, scene: &Scene, root: GameObjectId) -> Result<(), SerializationError> {
+        let Some(instance) = scene
+            .get_component::<PrefabInstance>(root)
+            .map(|instance| instance.clone())
+        else {
+            return Ok(());
+        };
+
+        let data = self.data.read().unwrap();
+        let changed: Vec<FieldPath> = data.changes[instance.synced_changes..]
+            .iter()
+            .filter(|(node, component, field)| !instance.overrides.covers(*node, component, field))
+            .cloned()
+            .collect();
+        self.write_fields(scene, &data.template, &instance, &changed)?;
+        if let Some(mut instance) = scene.get_component_mut::<PrefabInstance>(root) {
+            instance.synced_changes = data.changes.len();
+        }
+
+        Ok(())
+    }
+
+    /// Sets fields of the game objects of `instance` to their values in
+    /// the prefab merged with the overrides of the instance. Nothing is
+    /// written if one of the resulting components is invalid.
+    fn write_fields(
+        &self,
+        scene: &Scene,
+        template: &GameObjectTemplate,
+        instance: &PrefabInstance,
+        fields: &[FieldPath],
+    ) -> Result<(), SerializationError> {
+        let nodes = template.nodes();
+        let mut values: BTreeMap<(GameObjectId, &str), Value> = BTreeMap::new();
+        for (node, component, field) in fields {
+            let (Some(template_node), Some(id)) = (
+                nodes.get(*node).copied(),
+                instance.game_objects.get(*node).copied().flatten(),
+            ) else {
+                continue;
+            };
+            if scene.game_object(id).is_none() {
+                continue;
+            }
+            let mut value = match template_node.components.get(component) {
+                Some(value) => value.clone(),
+                // Neither the prefab nor the instance has the component
+                // anymore.
+                None if instance
+                    .overrides
+                    .component_entries(*node, component)
+                    .next()
+                    .is_none() =>
+                {
+                    continue
+                }
+                None => Value::Null,
+            };
+            instance
+                .overrides
+                .apply_component(*node, component, &mut value)?;
+
+            let current = match values.remove(&(id, component.as_str())) {
+                Some(current) => Some(current),
+                None => self.registry.component_value(scene, id, component)?,
+            };
+            let value = match current {
+                Some(mut current) => {
+                    *field_mut(component, &mut current, field)? =
+                        field_mut(component, &mut value, field)?.clone();
+                    current
+                }
+                None => value,
+            };
+            values.insert((id, component.as_str()), value);
+        }
+
+        let prepared = values
+            .into_iter()
+            .map(|((id, component), value)| Ok((id, self.registry.prepare(component, value)?)))
+            .collect::<Result<Vec<_>, SerializationError>>()?;
+        for (id, component) in prepared {
+            component(scene, id)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Added to the root of every prefab instance. It is saved along with the
+/// scene when the prefab is registered in the [`ComponentRegistry`].
+#[derive(Clone)]
+pub struct PrefabInstance {
+    prefab: Arc<Prefab>,
+    game_objects: Vec<Option<GameObjectId>>,
+    overrides: PrefabOverrides,
+    /// The number of changes of the prefab already written to the instance.
+    synced_changes: usize,
+}
+
+impl PrefabInstance {
+    pub fn prefab(&self) -> &Arc<Prefab> {
+        &self.prefab
+    }
+
+    /// The game objects created from the prefab, in pre-order. `None` for
+    /// the ones that were already removed when the instance was saved.
+    pub fn game_objects(&self) -> &[Option<GameObjectId>] {
+        &self.game_objects
+    }
+
+    pub fn overrides(&self) -> &PrefabOverrides {
+        &self.overrides
+    }
+}
+
+/// How a saved game object refers to the prefab it is an instance of.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(rename = "PrefabInstance", deny_unknown_fields)]
+pub(crate) struct PrefabLink {
+    prefab: String,
+    /// For every game object of the prefab, the position in the pre-order
+    /// of the saved instance of the game object created from it.
+    game_objects: Vec<Option<usize>>,
+    #[serde(default, skip_serializing_if = "PrefabOverrides::is_empty")]
+    overrides: PrefabOverrides,
+}
+
+impl PrefabLink {
+    /// Describes the instance rooted at `root`, if it is one.
+    pub(crate) fn new(
+        registry: &ComponentRegistry,
+        scene: &Scene,
+        root: GameObjectId,
+    ) -> Result<Option<Self>, SerializationError> {
+        let Some(instance) = scene
+            .get_component::<PrefabInstance>(root)
+            .map(|instance| instance.clone())
+        else {
+            return Ok(None);
+        };
+        let prefab = registry
+            .prefab_name(&instance.prefab)
+            .ok_or(SerializationError::UnregisteredPrefab(root))?;
+        let saved = scene.depth_first(Some(root));
+
+        Ok(Some(Self {
+            prefab: prefab.to_owned(),
+            game_objects: instance
+                .game_objects
+                .iter()
+                .map(|id| id.and_then(|id| saved.iter().position(|saved| *saved == id)))
+                .collect(),
+            overrides: instance.overrides,
+        }))
+    }
+
+    /// Checks that the prefab is registered, and returns a function linking
+    /// a loaded game object to it once its descendants are loaded.
+    pub(crate) fn prepare(
+        &self,
+        registry: &ComponentRegistry,
+    ) -> Result<PreparedComponent, SerializationError> {
+        let prefab = registry
+            .prefab(&self.prefab)
+            .ok_or_else(|| SerializationError::UnknownPrefab(self.prefab.clone()))?
+            .clone();
+        let link = self.clone();
+
+        Ok(Box::new(move |scene, root| {
+            let loaded = scene.depth_first(Some(root));
+            // The loaded values are the synced ones.
+            let synced_changes = prefab.data.read().unwrap().changes.len();
+            scene
+                .add_component(
+                    root,
+                    PrefabInstance {
+                        prefab,
+                        game_objects: link
+                            .game_objects
+                            .iter()
+                            .map(|index| index.and_then(|index| loaded.get(index).copied()))
+                            .collect(),
+                        overrides: link.overrides,
+                        synced_changes,
+                    },
+                )
+                .map(|_| ())
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde::Deserialize;
+    use serde::Serialize;
+    use util::math::vector::Vector3f;
+
+    use super::Prefab;
+    use super::PrefabInstance;
+    use super::PrefabOverrides;
+    use crate::scene::Scene;
+    use crate::serialization::ComponentRegistry;
+    use crate::serialization::SerializationError;
+    use crate::transform::Transform;
+
+    #[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+    struct Health {
+        current: u32,
+        max: u32,
+    }
+
+    const ENEMY: &str = r#"(
+        name: "enemy",
+        components: {"Health": (current: 10, max: 10)},
+        children: [(name: "weapon", transform: (position: (0.0, 1.0, 0.0), rotation: (0.0, 0.0, 0.0), scale: (1.0, 1.0, 1.0)))],
+    )"#;
+
+    #[test]
+    fn test_prefab_overrides_and_sync() {
+        let mut registry = ComponentRegistry::new();
+        registry.register::<Health>("Health");
+        let prefab = Prefab::load(&registry, ENEMY).unwrap();
+        let scene = Scene::new();
+
+        let plain = prefab.instantiate(&scene, PrefabOverrides::new()).unwrap();
+        let mut overrides = PrefabOverrides::new();
+        overrides
+            .set(0, "Health", "max", 50)
+            .set(0, "Transform", "position", (5.0, 0.0, 0.0));
+        let tough = prefab.instantiate(&scene, overrides).unwrap();
+
+        assert_eq!(4, scene.game_objects().len());
+        assert_eq!(10, scene.get_component::<Health>(plain).unwrap().max);
+        assert_eq!(50, scene.get_component::<Health>(tough).unwrap().max);
+        assert_eq!(
+            Vector3f::new(5.0, 0.0, 0.0),
+            scene.get_component::<Transform>(tough).unwrap().position()
+        );
+        let weapon = scene.children(tough)[0];
+        assert_eq!("weapon", scene.game_object(weapon).unwrap().name());
+
+        prefab
+            .set_field(
+                0,
+                "Health",
+                "",
+                Health {
+                    current: 20,
+                    max: 20,
+                },
+            )
+            .unwrap();
+        prefab.sync(&scene).unwrap();
+        assert_eq!(
+            Health {
+                current: 20,
+                max: 20
+            },
+            *scene.get_component::<Health>(plain).unwrap()
+        );
+        assert_eq!(
+            Health {
+                current: 20,
+                max: 50
+            },
+            *scene.get_component::<Health>(tough).unwrap()
+        );
+
+        prefab
+            .override_instance(&scene, tough, |overrides| {
+                overrides.remove(0, "Health", "max");
+            })
+            .unwrap();
+        assert_eq!(20, scene.get_component::<Health>(tough).unwrap().max);
+    }
+
+    #[test]
+    fn test_prefab_rejects_invalid_values() {
+        let mut registry = ComponentRegistry::new();
+        registry.register::<Health>("Health");
+        let prefab = Prefab::load(&registry, ENEMY).unwrap();
+        let scene = Scene::new();
+        let enemy = prefab.instantiate(&scene, PrefabOverrides::new()).unwrap();
+
+        assert!(matches!(
+            prefab.set_field(0, "Health", "max", "lots"),
+            Err(SerializationError::InvalidComponent { name, .. }) if name == "Health"
+        ));
+        prefab.sync(&scene).unwrap();
+        prefab.instantiate(&scene, PrefabOverrides::new()).unwrap();
+
+        assert!(matches!(
+            prefab.override_instance(&scene, enemy, |overrides| {
+                overrides
+                    .set(0, "Health", "current", 3)
+                    .set(0, "Health", "max", "lots");
+            }),
+            Err(SerializationError::InvalidComponent { name, .. }) if name == "Health"
+        ));
+        assert!(scene
+            .get_component::<PrefabInstance>(enemy)
+            .unwrap()
+            .overrides()
+            .is_empty());
+        assert_eq!(10, scene.get_component::<Health>(enemy).unwrap().current);
+    }
+
+    #[test]
+    fn test_prefab_sync_keeps_runtime_state() {
+        let mut registry = ComponentRegistry::new();
+        registry.register::<Health>("Health");
+        let prefab = Prefab::load(&registry, ENEMY).unwrap();
+        let scene = Scene::new();
+
+        let mut overrides = PrefabOverrides::new();
+        overrides.set(0, "Health", "current", 5);
+        let enemy = prefab.instantiate(&scene, overrides).unwrap();
+        let position = Vector3f::new(3.0, 0.0, 4.0);
+        scene
+            .get_component_mut::<Transform>(enemy)
+            .unwrap()
+            .set_position(position);
+
+        prefab.set_field(0, "Health", "max", 15).unwrap();
+        prefab.set_field(0, "Health", "current", 15).unwrap();
+        prefab.sync(&scene).unwrap();
+        assert_eq!(
+            position,
+            scene.get_component::<Transform>(enemy).unwrap().position()
+        );
+        assert_eq!(
+            Health {
+                current: 5,
+                max: 15
+            },
+            *scene.get_component::<Health>(enemy).unwrap()
+        );
+
+        // Already synced changes aren't written again.
+        scene.get_component_mut::<Health>(enemy).unwrap().max = 1;
+        prefab.sync(&scene).unwrap();
+        assert_eq!(1, scene.get_component::<Health>(enemy).unwrap().max);
+    }
+
+    #[test]
+    fn test_prefab_instance_round_trip() {
+        let mut registry = ComponentRegistry::new();
+        registry.register::<Health>("Health");
+        let prefab = Prefab::load(&registry, ENEMY).unwrap();
+        let scene = Scene::new();
+        let mut overrides = PrefabOverrides::new();
+        overrides.set(0, "Health", "max", 50);
+        let enemy = prefab.instantiate(&scene, overrides.clone()).unwrap();
+
+        assert!(matches!(
+            registry.save(&scene),
+            Err(SerializationError::UnregisteredPrefab(id)) if id == enemy
+        ));
+        registry.register_prefab("enemy", &prefab);
+        let text = registry.save(&scene).unwrap();
+        let loaded = registry.load_scene(&text).unwrap();
+        assert_eq!(text, registry.save(&loaded).unwrap());
+
+        let enemy = loaded.find_by_name("enemy").unwrap();
+        let weapon = loaded.find_by_name("weapon").unwrap();
+        {
+            let instance = loaded.get_component::<PrefabInstance>(enemy).unwrap();
+            assert!(Arc::ptr_eq(&prefab, instance.prefab()));
+            assert_eq!(&overrides, instance.overrides());
+            assert_eq!(&[Some(enemy), Some(weapon)], instance.game_objects());
+        }
+
+        prefab.set_field(0, "Health", "current", 3).unwrap();
+        prefab.sync(&loaded).unwrap();
+        assert_eq!(
+            Health {
+                current: 3,
+                max: 50
+            },
+            *loaded.get_component::<Health>(enemy).unwrap()
+        );
+
+        let mut registry = ComponentRegistry::new();
+        registry.register::<Health>("Health");
+        assert!(matches!(
+            registry.load_scene(&text),
+            Err(SerializationError::UnknownPrefab(name)) if name == "enemy"
+        ));
+    }
+
+    #[test]
+    fn test_prefab_errors() {
+        let mut registry = ComponentRegistry::new();
+        registry.register::<Health>("Health");
+        let prefab = Prefab::load(&registry, ENEMY).unwrap();
+        let scene = Scene::new();
+
+        let mut overrides = PrefabOverrides::new();
+        overrides.set(0, "Health", "armor", 3);
+        assert!(matches!(
+            prefab.instantiate(&scene, overrides),
+            Err(SerializationError::InvalidComponent { .. })
+        ));
+
+        let mut overrides = PrefabOverrides::new();
+        overrides.set(0, "Health", "max", "lots");
+        assert!(prefab.instantiate(&scene, overrides).is_err());
+    }
+
+    #[test]
+    fn test_prefab_from_game_object() {
+        let registry = ComponentRegistry::new();
+        let scene = Scene::new();
+        let root = scene.add_game_object();
+        scene.game_object(root).unwrap().set_name("crate");
+
+        let prefab = Prefab::from_game_object(&registry, &scene, root).unwrap();
+        let copy = prefab.instantiate(&scene, PrefabOverrides::new()).unwrap();
+        assert_eq!("crate", scene.game_object(copy).unwrap().name());
+    }
+}
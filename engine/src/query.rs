@@ -0,0 +1,365 @@
+use std::any::type_name;
+use std::any::TypeId;
+
+use crate::component::Component;
+use crate::component_storage::ComponentStorage;
+use crate::component_storage::Components;
+use crate::component_storage::SharedComponentStorage;
+use crate::component_storage::StorageMut;
+use crate::component_storage::StorageRef;
+use crate::resources::Resource;
+use crate::scene::GameObjectId;
+
+/// Whether something reads or writes the components of one type, or one
+/// [resource](crate::resources::Resources).
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ComponentAccess {
+    type_id: TypeId,
+    type_name: &'static str,
+    write: bool,
+    resource: bool,
+}
+
+impl ComponentAccess {
+    pub fn read<T: Component>() -> Self {
+        Self {
+            type_id: TypeId::of::<T>(),
+            type_name: type_name::<T>(),
+            write: false,
+            resource: false,
+        }
+    }
+
+    pub fn write<T: Component>() -> Self {
+        Self {
+            write: true,
+            ..Self::read::<T>()
+        }
+    }
+
+    pub fn read_resource<T: Resource>() -> Self {
+        Self {
+            type_id: TypeId::of::<T>(),
+            type_name: type_name::<T>(),
+            write: false,
+            resource: true,
+        }
+    }
+
+    pub fn write_resource<T: Resource>() -> Self {
+        Self {
+            write: true,
+            ..Self::read_resource::<T>()
+        }
+    }
+
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    pub fn is_write(&self) -> bool {
+        self.write
+    }
+
+    pub fn is_resource(&self) -> bool {
+        self.resource
+    }
+
+    pub fn conflicts_with(&self, other: &ComponentAccess) -> bool {
+        self.type_id == other.type_id
+            && self.resource == other.resource
+            && (self.write || other.write)
+    }
+}
+
+/// One element of a query: `&T` reads and `&mut T` writes the components of
+/// type `T`.
+pub trait Fetch {
+    type Component: Component;
+    type Guard<'s>;
+    type Item<'g>;
+
+    fn access() -> ComponentAccess;
+    /// # Safety
+    ///
+    /// This thread must have locked the storage for [`access`](Fetch::access);
+    /// the guard releases it.
+    unsafe fn guard(storage: &SharedComponentStorage<Self::Component>) -> Self::Guard<'_>;
+    fn storage<'g>(guard: &'g Self::Guard<'_>) -> &'g ComponentStorage<Self::Component>;
+    fn fetch<'g>(guard: &'g mut Self::Guard<'_>, id: GameObjectId) -> Option<Self::Item<'g>>;
+}
+
+impl<T: Component> Fetch for &T {
+    type Component = T;
+    type Guard<'s> = StorageRef<'s, T>;
+    type Item<'g> = &'g T;
+
+    fn access() -> ComponentAccess {
+        ComponentAccess::read::<T>()
+    }
+
+    unsafe fn guard(storage: &SharedComponentStorage<T>) -> Self::Guard<'_> {
+        StorageRef::locked(storage)
+    }
+
+    fn storage<'g>(guard: &'g Self::Guard<'_>) -> &'g ComponentStorage<T> {
+        guard
+    }
+
+    fn fetch<'g>(guard: &'g mut Self::Guard<'_>, id: GameObjectId) -> Option<Self::Item<'g>> {
+        guard.get(id)
+    }
+}
+
+impl<T: Component> Fetch for &mut T {
+    type Component = T;
+    type Guard<'s> = StorageMut<'s, T>;
+    type Item<'g> = &'g mut T;
+
+    fn access() -> ComponentAccess {
+        ComponentAccess::write::<T>()
+    }
+
+    unsafe fn guard(storage: &SharedComponentStorage<T>) -> Self::Guard<'_> {
+        StorageMut::locked(storage)
+    }
+
+    fn storage<'g>(guard: &'g Self::Guard<'_>) -> &'g ComponentStorage<T> {
+        guard
+    }
+
+    fn fetch<'g>(guard: &'g mut Self::Guard<'_>, id: GameObjectId) -> Option<Self::Item<'g>> {
+        guard.get_mut(id)
+    }
+}
+
+/// A tuple of [`Fetch`] elements, e.g. `(&mut Transform, &Velocity)`.
+pub trait QueryData {
+    type Storages;
+    type Guards<'s>;
+    type Item<'g>;
+
+    fn access() -> Vec<ComponentAccess>;
+    fn storages(components: &Components) -> Self::Storages;
+    /// Locks all storages at once, so that concurrent queries over
+    /// overlapping types can't deadlock each other.
+    fn lock(storages: &Self::Storages) -> Self::Guards<'_>;
+    /// The game objects of the smallest storage, a superset of the matches.
+    fn candidates(guards: &Self::Guards<'_>) -> Vec<GameObjectId>;
+    fn fetch<'g>(guards: &'g mut Self::Guards<'_>, id: GameObjectId) -> Option<Self::Item<'g>>;
+}
+
+macro_rules! impl_query_data {
+    ($($name:ident $index:tt),+) => {
+        impl<$($name: Fetch),+> QueryData for ($($name,)+) {
+            type Storages = ($(SharedComponentStorage<$name::Component>,)+);
+            type Guards<'s> = ($($name::Guard<'s>,)+);
+            type Item<'g> = ($($name::Item<'g>,)+);
+
+            fn access() -> Vec<ComponentAccess> {
+                vec![$($name::access()),+]
+            }
+
+            fn storages(components: &Components) -> Self::Storages {
+                ($(components.storage::<$name::Component>(),)+)
+            }
+
+            fn lock(storages: &Self::Storages) -> Self::Guards<'_> {
+                storages.0.lock_all(&Self::access());
+                // Every storage was just locked for its access.
+                unsafe { ($($name::guard(&storages.$index),)+) }
+            }
+
+            fn candidates(guards: &Self::Guards<'_>) -> Vec<GameObjectId> {
+                [$($name::storage(&guards.$index).ids()),+]
+                    .into_iter()
+                    .min_by_key(|ids| ids.len())
+                    .unwrap()
+                    .to_vec()
+            }
+
+            fn fetch<'g>(
+                guards: &'g mut Self::Guards<'_>,
+                id: GameObjectId,
+            ) -> Option<Self::Item<'g>> {
+                Some(($($name::fetch(&mut guards.$index, id)?,)+))
+            }
+        }
+    };
+}
+
+impl_query_data!(A 0);
+impl_query_data!(A 0, B 1);
+impl_query_data!(A 0, B 1, C 2);
+impl_query_data!(A 0, B 1, C 2, D 3);
+impl_query_data!(A 0, B 1, C 2, D 3, E 4);
+impl_query_data!(A 0, B 1, C 2, D 3, E 4, F 5);
+impl_query_data!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_query_data!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+/// The game objects having all components in `Q`. The storages are only
+/// locked while iterating, so a query may be kept around. Its callbacks may
+/// access further components under the rules of
+/// [`Components`](crate::component_storage::Components).
+pub struct Query<Q: QueryData> {
+    storages: Q::Storages,
+}
+
+impl<Q: QueryData> Query<Q> {
+    pub(crate) fn new(components: &Components) -> Self {
+        let access = Q::access();
+        for (i, a) in access.iter().enumerate() {
+            if let Some(b) = access[i + 1..].iter().find(|b| a.type_id == b.type_id) {
+                panic!("{} appears more than once in a query", b.type_name);
+            }
+        }
+
+        Self {
+            storages: Q::storages(components),
+        }
+    }
+
+    pub fn access() -> Vec<ComponentAccess> {
+        Q::access()
+    }
+
+    pub fn for_each<F>(&self, mut fun: F)
+    where
+        F: FnMut(GameObjectId, Q::Item<'_>),
+    {
+        let mut guards = Q::lock(&self.storages);
+        for id in Q::candidates(&guards) {
+            if let Some(item) = Q::fetch(&mut guards, id) {
+                fun(id, item);
+            }
+        }
+    }
+
+    /// Runs `fun` on the components of one game object, if it has all of
+    /// them.
+    pub fn get<R, F>(&self, id: GameObjectId, fun: F) -> Option<R>
+    where
+        F: FnOnce(Q::Item<'_>) -> R,
+    {
+        let mut guards = Q::lock(&self.storages);
+        Q::fetch(&mut guards, id).map(fun)
+    }
+
+    pub fn ids(&self) -> Vec<GameObjectId> {
+        let mut ids = Vec::new();
+        let mut guards = Q::lock(&self.storages);
+        for id in Q::candidates(&guards) {
+            if Q::fetch(&mut guards, id).is_some() {
+                ids.push(id);
+            }
+        }
+
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Barrier;
+    use std::thread;
+
+    use util::math::vector::Vector3f;
+
+    use crate::scene::Scene;
+    use crate::transform::Transform;
+
+    struct Velocity(Vector3f);
+
+    #[test]
+    fn test_query() {
+        let scene = Scene::new();
+        let moving = scene.add_game_object();
+        let still = scene.add_game_object();
+        scene
+            .add_component(moving, Velocity(Vector3f::new(1.0, 2.0, 3.0)))
+            .unwrap();
+
+        let query = scene.query::<(&mut Transform, &Velocity)>();
+        query.for_each(|_, (transform, velocity)| transform.translate(&velocity.0));
+        query.for_each(|_, (transform, velocity)| transform.translate(&velocity.0));
+
+        assert_eq!(vec![moving], query.ids());
+        assert_eq!(
+            Some(Vector3f::new(2.0, 4.0, 6.0)),
+            scene
+                .query::<(&Transform,)>()
+                .get(moving, |(transform,)| transform.position())
+        );
+        assert_eq!(
+            Vector3f::default(),
+            scene
+                .game_object(still)
+                .unwrap()
+                .transform()
+                .unwrap()
+                .position()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_query_rejects_aliasing() {
+        Scene::new().query::<(&mut Transform, &Transform)>();
+    }
+
+    #[test]
+    fn test_nested_access() {
+        let scene = Scene::new();
+        let id = scene.add_game_object();
+        scene
+            .add_component(id, Velocity(Vector3f::new(1.0, 0.0, 0.0)))
+            .unwrap();
+
+        let mut transform = scene.get_component_mut::<Transform>(id).unwrap();
+        scene.query::<(&Velocity,)>().for_each(|id, (velocity,)| {
+            assert!(scene.get_component::<Velocity>(id).is_some());
+            transform.translate(&velocity.0);
+        });
+        assert_eq!(Vector3f::new(1.0, 0.0, 0.0), transform.position());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_query_rejects_conflicting_access() {
+        let scene = Scene::new();
+        let id = scene.add_game_object();
+        scene
+            .query::<(&Transform,)>()
+            .for_each(|_, _| drop(scene.get_component_mut::<Transform>(id)));
+    }
+
+    #[test]
+    fn test_deadlock_panics() {
+        let scene = Scene::new();
+        let id = scene.add_game_object();
+        scene
+            .add_component(id, Velocity(Vector3f::default()))
+            .unwrap();
+        let barrier = Barrier::new(2);
+
+        let results = thread::scope(|scope| {
+            let transform_first = scope.spawn(|| {
+                let _transform = scene.get_component_mut::<Transform>(id);
+                barrier.wait();
+                drop(scene.get_component_mut::<Velocity>(id));
+            });
+            let velocity_first = scope.spawn(|| {
+                let _velocity = scene.get_component_mut::<Velocity>(id);
+                barrier.wait();
+                drop(scene.get_component_mut::<Transform>(id));
+            });
+
+            [transform_first.join(), velocity_first.join()]
+        });
+        assert_eq!(1, results.iter().filter(|result| result.is_err()).count());
+    }
+}
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use crate::scene::Scene;
 
 mod opengl_buffer;
@@ -5,15 +7,27 @@ pub mod opengl_renderer;
 mod opengl_shader;
 mod opengl_vertex_array;
 
+#[allow(clippy::all)]
 mod gl {
     include!(concat!(env!("OUT_DIR"), "/gl_bindings.rs"));
 }
 
 pub trait Renderer {
-    fn render(&self, scene: &Scene);
+    /// Draws `scenes` in order, usually
+    /// [`EngineContext::scenes`](crate::EngineContext::scenes), then runs
+    /// `passes` in order before presenting the frame.
+    fn render(&self, scenes: &[Arc<Scene>], passes: &[Arc<dyn RenderPass>]);
     fn resize(&self, width: usize, height: usize);
 }
 
+/// Extra drawing registered with
+/// [`Engine::add_render_pass`](crate::Engine::add_render_pass), e.g. by a
+/// plugin. Passes run on the render thread with the graphics context
+/// current.
+pub trait RenderPass: Send + Sync + 'static {
+    fn render(&self, scenes: &[Arc<Scene>]);
+}
+
 #[derive(PartialEq, Eq, Hash)]
 pub enum ShaderId {
     BuiltIn,
@@ -27,6 +27,7 @@ use super::opengl_shader::OpenGlShaderProgram;
 use super::opengl_vertex_array::OpenGlVertexArray;
 use super::opengl_vertex_array::OpenGlVertexArrayBuilder;
 use super::opengl_vertex_array::OpenGlVertexAttribPointer;
+use super::RenderPass;
 use super::Renderer;
 use super::ShaderId;
 use super::VaoId;
@@ -182,7 +183,7 @@ impl OpenGlRenderer {
 }
 
 impl Renderer for OpenGlRenderer {
-    fn render(&self, _scene: &Scene) {
+    fn render(&self, scenes: &[Arc<Scene>], passes: &[Arc<dyn RenderPass>]) {
         unsafe {
             self.shader_programs
                 .get(&ShaderId::BuiltIn)
@@ -195,6 +196,9 @@ impl Renderer for OpenGlRenderer {
             gl::Clear(gl::COLOR_BUFFER_BIT);
             gl::DrawArrays(gl::TRIANGLES, 0, 3);
         }
+        for pass in passes {
+            pass.render(scenes);
+        }
 
         self.gl_surface.swap_buffers(&self.gl_context).unwrap();
     }
@@ -0,0 +1,173 @@
+use std::any::Any;
+use std::any::TypeId;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::ops::DerefMut;
+use std::sync::Arc;
+use std::sync::RwLock;
+use std::sync::RwLockReadGuard;
+use std::sync::RwLockWriteGuard;
+
+pub trait Resource: Send + Sync + 'static {}
+
+impl<T: Send + Sync + 'static> Resource for T {}
+
+/// One value per type, shared by the whole engine, such as a score or an
+/// audio mixer. Every resource is behind its own lock, so jobs using
+/// different resources never contend, and systems declare their use with
+/// [`ComponentAccess::read_resource`](crate::query::ComponentAccess::read_resource)
+/// and [`write_resource`](crate::query::ComponentAccess::write_resource).
+pub struct Resources {
+    resources: RwLock<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
+}
+
+impl Resources {
+    pub fn new() -> Self {
+        Self {
+            resources: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn resource<T: Resource>(&self) -> Option<Arc<RwLock<T>>> {
+        self.resources
+            .read()
+            .unwrap()
+            .get(&TypeId::of::<T>())
+            .cloned()
+            .map(|resource| resource.downcast().unwrap())
+    }
+
+    /// Returns the previous value. Blocks while the resource is borrowed.
+    pub fn insert<T: Resource>(&self, value: T) -> Option<T> {
+        let resource = match self.resources.write().unwrap().entry(TypeId::of::<T>()) {
+            Entry::Occupied(entry) => entry.get().clone(),
+            Entry::Vacant(entry) => {
+                entry.insert(Arc::new(RwLock::new(value)));
+                return None;
+            }
+        };
+        let resource: Arc<RwLock<T>> = resource.downcast().unwrap();
+        let previous = std::mem::replace(&mut *resource.write().unwrap(), value);
+
+        Some(previous)
+    }
+
+    /// Returns whether the resource existed. Guards that are still alive keep
+    /// the value until they are dropped.
+    pub fn remove<T: Resource>(&self) -> bool {
+        self.resources
+            .write()
+            .unwrap()
+            .remove(&TypeId::of::<T>())
+            .is_some()
+    }
+
+    pub fn contains<T: Resource>(&self) -> bool {
+        self.resources
+            .read()
+            .unwrap()
+            .contains_key(&TypeId::of::<T>())
+    }
+
+    /// The returned guard locks the resource for reading until it is
+    /// dropped.
+    pub fn get<T: Resource>(&self) -> Option<ResourceRef<T>> {
+        self.resource::<T>().map(ResourceRef::new)
+    }
+
+    /// The returned guard locks the resource for writing until it is
+    /// dropped, so it must not be kept while accessing the same resource from
+    /// the same thread.
+    pub fn get_mut<T: Resource>(&self) -> Option<ResourceMut<T>> {
+        self.resource::<T>().map(ResourceMut::new)
+    }
+}
+
+impl Default for Resources {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct ResourceRef<T: Resource> {
+    guard: RwLockReadGuard<'static, T>,
+    _resource: Arc<RwLock<T>>,
+}
+
+impl<T: Resource> ResourceRef<T> {
+    fn new(resource: Arc<RwLock<T>>) -> Self {
+        // See ComponentRef::new.
+        let guard: RwLockReadGuard<'static, T> =
+            unsafe { std::mem::transmute(resource.read().unwrap()) };
+
+        Self {
+            guard,
+            _resource: resource,
+        }
+    }
+}
+
+impl<T: Resource> Deref for ResourceRef<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+pub struct ResourceMut<T: Resource> {
+    guard: RwLockWriteGuard<'static, T>,
+    _resource: Arc<RwLock<T>>,
+}
+
+impl<T: Resource> ResourceMut<T> {
+    fn new(resource: Arc<RwLock<T>>) -> Self {
+        // See ComponentRef::new.
+        let guard: RwLockWriteGuard<'static, T> =
+            unsafe { std::mem::transmute(resource.write().unwrap()) };
+
+        Self {
+            guard,
+            _resource: resource,
+        }
+    }
+}
+
+impl<T: Resource> Deref for ResourceMut<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<T: Resource> DerefMut for ResourceMut<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Resources;
+
+    #[derive(Debug, PartialEq)]
+    struct Score(u32);
+
+    #[test]
+    fn test_resources() {
+        let resources = Resources::new();
+        assert!(resources.get::<Score>().is_none());
+
+        assert_eq!(None, resources.insert(Score(1)));
+        let score = resources.get::<Score>().unwrap();
+        assert_eq!(Score(1), *score);
+        drop(score);
+
+        resources.get_mut::<Score>().unwrap().0 += 1;
+        assert_eq!(Some(Score(2)), resources.insert(Score(10)));
+        assert!(resources.remove::<Score>());
+        assert!(!resources.contains::<Score>());
+    }
+}
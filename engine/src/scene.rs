@@ -1,75 +1,901 @@
+use std::any::Any;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt::Display;
+use std::fmt::Formatter;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::MutexGuard;
+use std::sync::RwLock;
 use std::sync::Weak;
 
 use util::internal_mut_struct;
+use util::math::matrix::Matrix4x4f;
+use util::runtime_id::RuntimeId;
+use util::slot_map::SlotMap;
 
+use crate::component::Behaviour;
+use crate::component::Component;
 use crate::component::LogicComponent;
 use crate::component::LogicComponentFn;
+use crate::component_storage::ComponentMut;
+use crate::component_storage::ComponentRef;
+use crate::component_storage::Components;
+use crate::query::Query;
+use crate::query::QueryData;
+use crate::transform::Transform;
+use crate::EngineContext;
+
+pub type GameObjectId = RuntimeId;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SceneError {
+    InvalidGameObject(GameObjectId),
+    CyclicHierarchy {
+        child: GameObjectId,
+        parent: GameObjectId,
+    },
+}
+
+impl Display for SceneError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            SceneError::InvalidGameObject(id) => write!(f, "game object {id} does not exist"),
+            SceneError::CyclicHierarchy { child, parent } => write!(
+                f,
+                "attaching {child} to {parent} would make it its own ancestor"
+            ),
+        }
+    }
+}
+
+impl Error for SceneError {}
 
 struct SceneImpl {
-    game_objects: Vec<Arc<GameObject>>,
+    game_objects: SlotMap<Arc<GameObject>>,
+    inactive: HashSet<GameObjectId>,
+    pending_destroy: Vec<GameObjectId>,
 }
 
-internal_mut_struct!(Scene, SceneImpl, this: Weak<Scene>);
+impl SceneImpl {
+    fn game_object(&self, id: GameObjectId) -> Result<&Arc<GameObject>, SceneError> {
+        self.game_objects
+            .get(id)
+            .ok_or(SceneError::InvalidGameObject(id))
+    }
+
+    fn children(&self, id: GameObjectId) -> Vec<GameObjectId> {
+        self.game_objects
+            .get(id)
+            .map(|game_object| game_object.children())
+            .unwrap_or_default()
+    }
+
+    fn roots(&self) -> Vec<GameObjectId> {
+        self.game_objects
+            .iter()
+            .filter(|(_, game_object)| game_object.parent().is_none())
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    fn subtree(&self, root: GameObjectId) -> Vec<GameObjectId> {
+        let mut stack = vec![root];
+        let mut visited = Vec::new();
+        while let Some(id) = stack.pop() {
+            if self.game_objects.contains(id) {
+                visited.push(id);
+                stack.extend(self.children(id).into_iter().rev());
+            }
+        }
+
+        visited
+    }
+
+    /// Recomputes whether `root` and its descendants are active, i.e. enabled
+    /// along with all of their ancestors.
+    fn refresh_active(&mut self, root: GameObjectId) {
+        let mut stack = vec![(
+            root,
+            self.game_objects
+                .get(root)
+                .and_then(|game_object| game_object.parent())
+                .is_none_or(|parent| !self.inactive.contains(&parent)),
+        )];
+        while let Some((id, parent_active)) = stack.pop() {
+            let Some(game_object) = self.game_objects.get(id) else {
+                continue;
+            };
+
+            let game_object_inner = game_object.lock_inner();
+            let active = parent_active && game_object_inner.enabled;
+            if active {
+                self.inactive.remove(&id);
+            } else {
+                self.inactive.insert(id);
+            }
+            stack.extend(
+                game_object_inner
+                    .children
+                    .iter()
+                    .map(|child| (*child, active)),
+            );
+        }
+    }
+
+    fn unlink_from_parent(&self, game_object: &GameObject) {
+        let parent = game_object.lock_inner().parent.take();
+        if let Some(parent) = parent.and_then(|parent| self.game_objects.get(parent)) {
+            parent
+                .lock_inner()
+                .children
+                .retain(|child| *child != game_object.id);
+        }
+    }
+}
+
+internal_mut_struct!(RwLock; Scene, SceneImpl, this: Weak<Scene>, components: Components);
 
 impl Scene {
     pub fn new() -> Arc<Self> {
         Arc::new_cyclic(|this| Self {
-            inner: Mutex::new(SceneImpl {
-                game_objects: Vec::new(),
+            inner: RwLock::new(SceneImpl {
+                game_objects: SlotMap::new(),
+                inactive: HashSet::new(),
+                pending_destroy: Vec::new(),
             }),
             this: this.clone(),
+            components: Components::new(),
         })
     }
 
-    pub fn add_game_object(&self) -> Arc<GameObject> {
-        let game_object = GameObject::new(self.this.clone());
-        self.lock_inner().game_objects.push(game_object.clone());
+    /// New game objects start with a default [`Transform`] component.
+    pub fn add_game_object(&self) -> GameObjectId {
+        let id = self
+            .write_inner()
+            .game_objects
+            .insert_with(|id| GameObject::new(id, self.this.clone()));
+        self.components
+            .storage::<Transform>()
+            .write()
+            .insert(id, Transform::default());
+
+        id
+    }
+
+    /// Returns `None` if the id is stale.
+    pub fn game_object(&self, id: GameObjectId) -> Option<Arc<GameObject>> {
+        self.read_inner().game_objects.get(id).cloned()
+    }
+
+    pub fn game_objects(&self) -> Vec<GameObjectId> {
+        self.read_inner().game_objects.ids().collect()
+    }
+
+    /// Every game object along with whether it is active.
+    pub(crate) fn game_object_snapshot(&self) -> Vec<(Arc<GameObject>, bool)> {
+        let inner = self.read_inner();
+        inner
+            .game_objects
+            .iter()
+            .map(|(id, game_object)| (game_object.clone(), !inner.inactive.contains(&id)))
+            .collect()
+    }
+
+    /// The game objects [`remove_destroyed`](Scene::remove_destroyed) is
+    /// going to remove, parents before children.
+    pub fn destroyed_game_objects(&self) -> Vec<Arc<GameObject>> {
+        let inner = self.read_inner();
+        let mut visited = HashSet::new();
+        inner
+            .pending_destroy
+            .iter()
+            .flat_map(|root| inner.subtree(*root))
+            .filter(|id| visited.insert(*id))
+            .filter_map(|id| inner.game_objects.get(id).cloned())
+            .collect()
+    }
+
+    /// Marks the game object and all of its descendants for destruction.
+    /// They stay alive until [`remove_destroyed`](Scene::remove_destroyed),
+    /// which the engine calls at the end of each update.
+    pub fn destroy(&self, id: GameObjectId) -> Result<(), SceneError> {
+        let mut inner = self.write_inner();
+        let mut game_object_inner = inner.game_object(id)?.lock_inner();
+        if !game_object_inner.pending_destroy {
+            game_object_inner.pending_destroy = true;
+            drop(game_object_inner);
+            inner.pending_destroy.push(id);
+        }
+
+        Ok(())
+    }
+
+    /// Removes the game objects marked by [`destroy`](Scene::destroy),
+    /// their descendants and all of their components. Returns the removed
+    /// ids, parents before children.
+    pub fn remove_destroyed(&self) -> Vec<GameObjectId> {
+        let removed = {
+            let mut inner = self.write_inner();
+            let mut removed = Vec::new();
+            let mut visited = HashSet::new();
+            for root in std::mem::take(&mut inner.pending_destroy) {
+                if let Ok(game_object) = inner.game_object(root) {
+                    inner.unlink_from_parent(game_object);
+                }
+                for id in inner.subtree(root) {
+                    if visited.insert(id) {
+                        removed.push(id);
+                    }
+                }
+            }
+
+            for id in &removed {
+                inner.game_objects.remove(*id);
+                inner.inactive.remove(id);
+            }
+
+            removed
+        };
+
+        for id in &removed {
+            self.components.remove_all(*id);
+        }
+
+        removed
+    }
+
+    /// Disabling a game object deactivates its whole subtree; see
+    /// [`is_active`](Scene::is_active).
+    pub fn set_enabled(&self, id: GameObjectId, enabled: bool) -> Result<(), SceneError> {
+        let mut inner = self.write_inner();
+        inner.game_object(id)?.lock_inner().enabled = enabled;
+        inner.refresh_active(id);
+
+        Ok(())
+    }
+
+    /// Whether the game object and all of its ancestors are enabled. Logic
+    /// and systems skip inactive game objects, and renderers should too.
+    pub fn is_active(&self, id: GameObjectId) -> bool {
+        let inner = self.read_inner();
+        inner.game_objects.contains(id) && !inner.inactive.contains(&id)
+    }
+
+    pub fn inactive_game_objects(&self) -> HashSet<GameObjectId> {
+        self.read_inner().inactive.clone()
+    }
+
+    pub fn find_by_name(&self, name: &str) -> Option<GameObjectId> {
+        self.read_inner()
+            .game_objects
+            .iter()
+            .find(|(_, game_object)| game_object.lock_inner().name == name)
+            .map(|(id, _)| id)
+    }
+
+    pub fn find_by_tag(&self, tag: &str) -> Vec<GameObjectId> {
+        self.read_inner()
+            .game_objects
+            .iter()
+            .filter(|(_, game_object)| game_object.has_tag(tag))
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// Adds `component` to the game object, returning the component of the
+    /// same type it replaced, if any.
+    pub fn add_component<T: Component>(
+        &self,
+        id: GameObjectId,
+        component: T,
+    ) -> Result<Option<T>, SceneError> {
+        self.read_inner().game_object(id)?;
+
+        Ok(self.components.storage::<T>().write().insert(id, component))
+    }
+
+    pub fn remove_component<T: Component>(&self, id: GameObjectId) -> Option<T> {
+        self.components.storage::<T>().write().remove(id)
+    }
 
-        game_object
+    pub fn has_component<T: Component>(&self, id: GameObjectId) -> bool {
+        self.components.storage::<T>().read().contains(id)
     }
 
-    pub fn game_objects(&self) -> Vec<Arc<GameObject>> {
-        self.lock_inner().game_objects.to_vec()
+    /// The returned guard locks every `T` in the scene for reading until it
+    /// is dropped.
+    pub fn get_component<T: Component>(&self, id: GameObjectId) -> Option<ComponentRef<T>> {
+        ComponentRef::new(self.components.storage::<T>(), id)
     }
+
+    /// The returned guard locks every `T` in the scene for writing until it
+    /// is dropped.
+    pub fn get_component_mut<T: Component>(&self, id: GameObjectId) -> Option<ComponentMut<T>> {
+        ComponentMut::new(self.components.storage::<T>(), id)
+    }
+
+    /// Iterates the game objects having all components in `Q`, e.g.
+    /// `scene.query::<(&mut Transform, &Velocity)>().for_each(|id, (t, v)| ..)`.
+    ///
+    /// Panics if a component type appears more than once in `Q`.
+    pub fn query<Q: QueryData>(&self) -> Query<Q> {
+        Query::new(&self.components)
+    }
+
+    /// Makes `child` a child of `parent`, detaching it from its previous
+    /// parent. The child keeps its local transform, so it moves along with
+    /// its new parent.
+    pub fn attach(&self, child: GameObjectId, parent: GameObjectId) -> Result<(), SceneError> {
+        let mut inner = self.write_inner();
+        let child_object = inner.game_object(child)?;
+        let parent_object = inner.game_object(parent)?;
+
+        let mut ancestor = Some(parent);
+        while let Some(id) = ancestor {
+            if id == child {
+                return Err(SceneError::CyclicHierarchy { child, parent });
+            }
+            ancestor = inner.game_objects.get(id).and_then(|o| o.parent());
+        }
+
+        inner.unlink_from_parent(child_object);
+        {
+            let mut child_inner = child_object.lock_inner();
+            child_inner.parent = Some(parent);
+            child_inner.hierarchy_changed = true;
+        }
+        parent_object.lock_inner().children.push(child);
+        inner.refresh_active(child);
+
+        Ok(())
+    }
+
+    /// Turns `child` into a root object.
+    pub fn detach(&self, child: GameObjectId) -> Result<(), SceneError> {
+        let mut inner = self.write_inner();
+        let child_object = inner.game_object(child)?;
+        inner.unlink_from_parent(child_object);
+        child_object.lock_inner().hierarchy_changed = true;
+        inner.refresh_active(child);
+
+        Ok(())
+    }
+
+    pub fn parent(&self, id: GameObjectId) -> Option<GameObjectId> {
+        self.read_inner().game_objects.get(id)?.parent()
+    }
+
+    pub fn children(&self, id: GameObjectId) -> Vec<GameObjectId> {
+        self.read_inner().children(id)
+    }
+
+    pub fn roots(&self) -> Vec<GameObjectId> {
+        self.read_inner().roots()
+    }
+
+    /// Pre-order traversal of the subtree under `root`, or of the whole
+    /// scene if `root` is `None`. Siblings are visited in attachment order.
+    pub fn depth_first(&self, root: Option<GameObjectId>) -> Vec<GameObjectId> {
+        let inner = self.read_inner();
+        let mut stack = match root {
+            Some(root) => vec![root],
+            None => inner.roots(),
+        };
+        stack.reverse();
+
+        let mut visited = Vec::new();
+        while let Some(id) = stack.pop() {
+            if inner.game_objects.contains(id) {
+                visited.push(id);
+                stack.extend(inner.children(id).into_iter().rev());
+            }
+        }
+
+        visited
+    }
+
+    /// Level-order traversal of the subtree under `root`, or of the whole
+    /// scene if `root` is `None`.
+    pub fn breadth_first(&self, root: Option<GameObjectId>) -> Vec<GameObjectId> {
+        let inner = self.read_inner();
+        let mut queue: VecDeque<GameObjectId> = match root {
+            Some(root) => VecDeque::from([root]),
+            None => inner.roots().into(),
+        };
+
+        let mut visited = Vec::new();
+        while let Some(id) = queue.pop_front() {
+            if inner.game_objects.contains(id) {
+                visited.push(id);
+                queue.extend(inner.children(id));
+            }
+        }
+
+        visited
+    }
+
+    /// Recomputes the world matrix of every game object whose transform, or
+    /// the transform of one of its ancestors, changed since the last update.
+    /// Game objects without a [`Transform`] pass their parent's world matrix
+    /// on to their children.
+    pub fn update_transforms(&self) {
+        // (id, index of the parent in `order`, re-parented since last update)
+        let mut order: Vec<(GameObjectId, Option<usize>, bool)> = Vec::new();
+        {
+            let inner = self.read_inner();
+            let mut stack: Vec<(GameObjectId, Option<usize>)> = inner
+                .roots()
+                .into_iter()
+                .rev()
+                .map(|id| (id, None))
+                .collect();
+            while let Some((id, parent)) = stack.pop() {
+                let Some(game_object) = inner.game_objects.get(id) else {
+                    continue;
+                };
+
+                let mut game_object_inner = game_object.lock_inner();
+                let index = order.len();
+                order.push((
+                    id,
+                    parent,
+                    std::mem::take(&mut game_object_inner.hierarchy_changed),
+                ));
+                for child in game_object_inner.children.iter().rev() {
+                    stack.push((*child, Some(index)));
+                }
+            }
+        }
+
+        let transforms = self.components.storage::<Transform>();
+        let mut transforms = transforms.write();
+        let mut world_matrices: Vec<Option<Matrix4x4f>> = Vec::with_capacity(order.len());
+        let mut changed: Vec<bool> = Vec::with_capacity(order.len());
+        for (id, parent, hierarchy_changed) in order {
+            let parent_world_matrix = parent.and_then(|parent| world_matrices[parent].clone());
+            let parent_changed = parent.is_some_and(|parent| changed[parent]);
+            let is_changed = match transforms.get_mut(id) {
+                Some(transform) => {
+                    let is_changed = hierarchy_changed || parent_changed || transform.is_dirty();
+                    if is_changed {
+                        transform.update_world_matrix(parent_world_matrix.as_ref());
+                    }
+                    world_matrices.push(Some(transform.world_matrix().clone()));
+                    is_changed
+                }
+                None => {
+                    world_matrices.push(parent_world_matrix);
+                    hierarchy_changed || parent_changed
+                }
+            };
+            changed.push(is_changed);
+        }
+    }
+}
+
+pub(crate) type SharedBehaviour = Arc<Mutex<dyn Behaviour>>;
+
+struct BehaviourState {
+    behaviour: SharedBehaviour,
+    started: bool,
+    enabled: bool,
+}
+
+enum BehaviourEvent {
+    Enable,
+    Disable,
+    Start,
 }
 
 struct GameObjectImpl {
-    logic_component: Option<Arc<LogicComponent>>,
+    name: String,
+    tags: Vec<String>,
+    enabled: bool,
+    pending_destroy: bool,
+    behaviours: Vec<BehaviourState>,
+    hierarchy_changed: bool,
+    parent: Option<GameObjectId>,
+    children: Vec<GameObjectId>,
 }
 
 internal_mut_struct!(
     GameObject,
     GameObjectImpl,
+    id: GameObjectId,
     this: Weak<GameObject>,
     scene: Weak<Scene>
 );
 
 impl GameObject {
-    fn new(scene: Weak<Scene>) -> Arc<Self> {
+    fn new(id: GameObjectId, scene: Weak<Scene>) -> Arc<Self> {
         Arc::new_cyclic(|this| Self {
             inner: Mutex::new(GameObjectImpl {
-                logic_component: None,
+                name: String::new(),
+                tags: Vec::new(),
+                enabled: true,
+                pending_destroy: false,
+                behaviours: Vec::new(),
+                hierarchy_changed: false,
+                parent: None,
+                children: Vec::new(),
             }),
+            id,
             this: this.clone(),
             scene,
         })
     }
 
+    pub fn id(&self) -> GameObjectId {
+        self.id
+    }
+
+    pub fn name(&self) -> String {
+        self.lock_inner().name.clone()
+    }
+
+    pub fn set_name<T: Into<String>>(&self, name: T) {
+        self.lock_inner().name = name.into();
+    }
+
+    pub fn tags(&self) -> Vec<String> {
+        self.lock_inner().tags.clone()
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.lock_inner().tags.iter().any(|t| t == tag)
+    }
+
+    pub fn add_tag<T: Into<String>>(&self, tag: T) {
+        let tag = tag.into();
+        let mut inner = self.lock_inner();
+        if !inner.tags.contains(&tag) {
+            inner.tags.push(tag);
+        }
+    }
+
+    pub fn remove_tag(&self, tag: &str) {
+        self.lock_inner().tags.retain(|t| t != tag);
+    }
+
+    /// Whether this game object itself is enabled, regardless of its
+    /// ancestors; see [`Scene::is_active`].
+    pub fn is_enabled(&self) -> bool {
+        self.lock_inner().enabled
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        // Only fails if the game object was already removed from the scene.
+        let _ = self.scene().set_enabled(self.id, enabled);
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.scene().is_active(self.id)
+    }
+
+    pub fn destroy(&self) {
+        // Only fails if the game object was already removed from the scene.
+        let _ = self.scene().destroy(self.id);
+    }
+
+    pub fn is_pending_destroy(&self) -> bool {
+        self.lock_inner().pending_destroy
+    }
+
+    pub fn parent(&self) -> Option<GameObjectId> {
+        self.lock_inner().parent
+    }
+
+    pub fn children(&self) -> Vec<GameObjectId> {
+        self.lock_inner().children.clone()
+    }
+
+    /// Returns `None` if the [`Transform`] component was removed.
+    pub fn transform(&self) -> Option<Transform> {
+        self.get_component::<Transform>()
+            .map(|transform| transform.clone())
+    }
+
+    /// Runs `fun` on a copy of the transform and stores it back afterwards,
+    /// so `fun` may access any component. Returns `None` if the
+    /// [`Transform`] component was removed.
+    pub fn update_transform<R, F: FnOnce(&mut Transform) -> R>(&self, fun: F) -> Option<R> {
+        let mut transform = self.transform()?;
+        let result = fun(&mut transform);
+        *self.get_component_mut::<Transform>()? = transform;
+
+        Some(result)
+    }
+
+    pub(crate) fn scene(&self) -> Arc<Scene> {
+        self.scene
+            .upgrade()
+            .expect("game object outlived its scene")
+    }
+
+    pub fn add_component<T: Component>(&self, component: T) -> Option<T> {
+        self.scene()
+            .add_component(self.id, component)
+            .expect("game object was removed from its scene")
+    }
+
+    pub fn remove_component<T: Component>(&self) -> Option<T> {
+        self.scene().remove_component(self.id)
+    }
+
+    pub fn has_component<T: Component>(&self) -> bool {
+        self.scene().has_component::<T>(self.id)
+    }
+
+    pub fn get_component<T: Component>(&self) -> Option<ComponentRef<T>> {
+        self.scene().get_component(self.id)
+    }
+
+    pub fn get_component_mut<T: Component>(&self) -> Option<ComponentMut<T>> {
+        self.scene().get_component_mut(self.id)
+    }
+
+    /// Runs `fun` on several components of this game object, locked
+    /// together, e.g.
+    /// `game_object.query::<(&mut Transform, &Velocity), _, _>(|(t, v)| ..)`.
+    /// Returns `None` if it lacks one of them.
+    pub fn query<Q: QueryData, R, F: FnOnce(Q::Item<'_>) -> R>(&self, fun: F) -> Option<R> {
+        self.scene().query::<Q>().get(self.id, fun)
+    }
+
+    /// Behaviours run in the order they were added, starting with the next
+    /// frame; see [`Behaviour`] for the order of the hooks.
+    pub fn add_behaviour<T: Behaviour>(&self, behaviour: T) {
+        self.lock_inner().behaviours.push(BehaviourState {
+            behaviour: Arc::new(Mutex::new(behaviour)),
+            started: false,
+            enabled: false,
+        });
+    }
+
     pub fn add_logic_component<T>(&self, fun: T)
     where
         T: LogicComponentFn,
     {
-        self.lock_inner().logic_component = Some(LogicComponent::new(self.this.clone(), fun));
+        self.add_behaviour(LogicComponent::new(fun));
+    }
+
+    /// Runs `fun` on the first behaviour of type `T`. Returns `None` if there
+    /// is none, or if it is currently running, e.g. when a behaviour looks
+    /// itself up.
+    pub fn with_behaviour<T: Behaviour, R, F: FnOnce(&mut T) -> R>(&self, fun: F) -> Option<R> {
+        let behaviours: Vec<SharedBehaviour> = self
+            .lock_inner()
+            .behaviours
+            .iter()
+            .map(|state| state.behaviour.clone())
+            .collect();
+
+        for behaviour in behaviours {
+            let Ok(mut behaviour) = behaviour.try_lock() else {
+                continue;
+            };
+            let behaviour: &mut dyn Any = &mut *behaviour;
+            if let Some(behaviour) = behaviour.downcast_mut::<T>() {
+                return Some(fun(behaviour));
+            }
+        }
+
+        None
+    }
+
+    /// Calls `on_enable`, `on_disable` and `on_start` as needed to bring the
+    /// behaviours in line with whether the game object is `active`.
+    pub(crate) fn refresh_behaviours(&self, engine_context: &EngineContext, active: bool) {
+        let mut events = Vec::new();
+        for state in self.lock_inner().behaviours.iter_mut() {
+            if state.enabled != active {
+                state.enabled = active;
+                let event = if active {
+                    BehaviourEvent::Enable
+                } else {
+                    BehaviourEvent::Disable
+                };
+                events.push((state.behaviour.clone(), event));
+            }
+            if active && !state.started {
+                state.started = true;
+                events.push((state.behaviour.clone(), BehaviourEvent::Start));
+            }
+        }
+
+        for (behaviour, event) in events {
+            let mut behaviour = behaviour.lock().unwrap();
+            match event {
+                BehaviourEvent::Enable => behaviour.on_enable(engine_context, self),
+                BehaviourEvent::Disable => behaviour.on_disable(engine_context, self),
+                BehaviourEvent::Start => behaviour.on_start(engine_context, self),
+            }
+        }
+    }
+
+    /// The behaviours that are enabled and have been started.
+    pub(crate) fn running_behaviours(&self) -> Vec<SharedBehaviour> {
+        self.lock_inner()
+            .behaviours
+            .iter()
+            .filter(|state| state.started && state.enabled)
+            .map(|state| state.behaviour.clone())
+            .collect()
+    }
+
+    /// Calls `on_disable` if needed and `on_destroy` on every behaviour, then
+    /// drops them.
+    pub(crate) fn destroy_behaviours(&self, engine_context: &EngineContext) {
+        let behaviours = std::mem::take(&mut self.lock_inner().behaviours);
+        for state in behaviours {
+            let mut behaviour = state.behaviour.lock().unwrap();
+            if state.enabled {
+                behaviour.on_disable(engine_context, self);
+            }
+            behaviour.on_destroy(engine_context, self);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use util::math::vector::Vector3f;
+
+    use super::Scene;
+    use super::SceneError;
+    use crate::transform::Transform;
+
+    #[test]
+    fn test_hierarchy() {
+        let scene = Scene::new();
+        let root = scene.add_game_object();
+        let child = scene.add_game_object();
+        let grandchild = scene.add_game_object();
+        let other = scene.add_game_object();
+
+        scene.attach(child, root).unwrap();
+        scene.attach(grandchild, child).unwrap();
+        assert_eq!(
+            Err(SceneError::CyclicHierarchy {
+                child: root,
+                parent: grandchild
+            }),
+            scene.attach(root, grandchild)
+        );
+
+        assert_eq!(
+            vec![root, child, grandchild, other],
+            scene.depth_first(None)
+        );
+        assert_eq!(
+            vec![root, other, child, grandchild],
+            scene.breadth_first(None)
+        );
+
+        scene.detach(child).unwrap();
+        assert_eq!(vec![root, child, other], scene.roots());
+        assert!(scene.children(root).is_empty());
+    }
+
+    #[test]
+    fn test_components() {
+        #[derive(PartialEq, Debug)]
+        struct Health(u32);
+        struct Name(&'static str);
+
+        let scene = Scene::new();
+        let a = scene.add_game_object();
+        let b = scene.add_game_object();
+        let a_object = scene.game_object(a).unwrap();
+
+        assert_eq!(None, a_object.add_component(Health(10)));
+        a_object.add_component(Name("a"));
+        scene.add_component(b, Health(20)).unwrap();
+        assert_eq!(Some(Health(10)), a_object.add_component(Health(15)));
+
+        a_object.get_component_mut::<Health>().unwrap().0 -= 5;
+        assert_eq!(10, a_object.get_component::<Health>().unwrap().0);
+        assert_eq!("a", a_object.get_component::<Name>().unwrap().0);
+        assert!(scene.get_component::<Name>(b).is_none());
+
+        assert_eq!(Some(Health(10)), a_object.remove_component::<Health>());
+        assert!(!a_object.has_component::<Health>());
+        assert!(a_object.has_component::<Name>());
+        assert_eq!(20, scene.get_component::<Health>(b).unwrap().0);
+
+        let b_object = scene.game_object(b).unwrap();
+        b_object.add_component(Name("b"));
+        assert_eq!(
+            Some("b 20"),
+            b_object
+                .query::<(&mut Health, &Name), _, _>(|(health, name)| {
+                    health.0 += 1;
+                    format!("{} {}", name.0, health.0 - 1)
+                })
+                .as_deref()
+        );
+        assert_eq!(None, a_object.query::<(&Health,), _, _>(|_| ()));
     }
 
-    pub fn remove_logic_component(&self) {
-        self.lock_inner().logic_component = None;
+    #[test]
+    fn test_destroy() {
+        let scene = Scene::new();
+        let root = scene.add_game_object();
+        let child = scene.add_game_object();
+        let grandchild = scene.add_game_object();
+        let other = scene.add_game_object();
+        scene.attach(child, root).unwrap();
+        scene.attach(grandchild, child).unwrap();
+        scene.add_component(grandchild, 7u32).unwrap();
+
+        scene.destroy(child).unwrap();
+        scene.destroy(grandchild).unwrap();
+        assert!(scene.game_object(child).unwrap().is_pending_destroy());
+        assert_eq!(4, scene.game_objects().len());
+
+        assert_eq!(vec![child, grandchild], scene.remove_destroyed());
+        assert_eq!(vec![root, other], scene.game_objects());
+        assert!(scene.children(root).is_empty());
+        assert!(scene.get_component::<u32>(grandchild).is_none());
+        assert_eq!(
+            Err(SceneError::InvalidGameObject(child)),
+            scene.destroy(child)
+        );
     }
 
-    pub fn logic_component(&self) -> Option<Arc<LogicComponent>> {
-        self.lock_inner().logic_component.clone()
+    #[test]
+    fn test_enabled_and_lookup() {
+        let scene = Scene::new();
+        let root = scene.add_game_object();
+        let child = scene.add_game_object();
+        scene.attach(child, root).unwrap();
+
+        let root_object = scene.game_object(root).unwrap();
+        let child_object = scene.game_object(child).unwrap();
+        root_object.set_name("player");
+        child_object.add_tag("weapon");
+
+        root_object.set_enabled(false);
+        assert!(!scene.is_active(child));
+        assert!(child_object.is_enabled());
+
+        scene.detach(child).unwrap();
+        assert!(scene.is_active(child));
+        assert!(!scene.is_active(root));
+
+        assert_eq!(Some(root), scene.find_by_name("player"));
+        assert_eq!(None, scene.find_by_name("enemy"));
+        assert_eq!(vec![child], scene.find_by_tag("weapon"));
+    }
+
+    #[test]
+    fn test_transform_propagation() {
+        let scene = Scene::new();
+        let parent = scene.add_game_object();
+        let child = scene.add_game_object();
+        scene.attach(child, parent).unwrap();
+
+        let parent_object = scene.game_object(parent).unwrap();
+        let child_object = scene.game_object(child).unwrap();
+        parent_object.update_transform(|t| t.set_position(Vector3f::new(1.0, 0.0, 0.0)));
+        child_object.update_transform(|t| t.set_position(Vector3f::new(0.0, 2.0, 0.0)));
+        scene.update_transforms();
+        assert_eq!(
+            Vector3f::new(1.0, 2.0, 0.0),
+            child_object.transform().unwrap().world_position()
+        );
+
+        parent_object.update_transform(|t| t.set_scale(Vector3f::new(3.0, 3.0, 3.0)));
+        scene.update_transforms();
+        assert_eq!(
+            Vector3f::new(1.0, 6.0, 0.0),
+            child_object.transform().unwrap().world_position()
+        );
+
+        parent_object.remove_component::<Transform>();
+        assert!(parent_object.transform().is_none());
+        assert_eq!(None, parent_object.update_transform(|_| ()));
     }
 }
@@ -0,0 +1,802 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::sync::Arc;
+
+use ron::ser::PrettyConfig;
+use serde::de::DeserializeOwned;
+use serde::de::MapAccess;
+use serde::de::Visitor;
+use serde::ser::SerializeMap;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+use serde_json::Value;
+use util::math::vector::Vector3f;
+
+use crate::component::Component;
+use crate::prefab::Prefab;
+use crate::prefab::PrefabLink;
+use crate::scene::GameObjectId;
+use crate::scene::Scene;
+use crate::scene::SceneError;
+use crate::transform::Transform;
+
+/// The version written by [`ComponentRegistry::save`]. Bump it and migrate
+/// older documents in [`ComponentRegistry::load`] when the format changes.
+pub const SCENE_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug)]
+pub enum SerializationError {
+    /// Invalid RON, with the position of the problem.
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+    UnsupportedVersion {
+        found: u32,
+        supported: u32,
+    },
+    UnknownComponent(String),
+    UnknownPrefab(String),
+    /// Saving a prefab instance whose prefab has no name in the registry.
+    UnregisteredPrefab(GameObjectId),
+    /// A component value that doesn't match its type, e.g. after a prefab
+    /// override.
+    InvalidComponent {
+        name: String,
+        message: String,
+    },
+    Scene(SceneError),
+}
+
+impl Display for SerializationError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            SerializationError::Parse(error) => write!(f, "invalid scene: {error}"),
+            SerializationError::Serialize(error) => write!(f, "cannot save scene: {error}"),
+            SerializationError::UnsupportedVersion { found, supported } => write!(
+                f,
+                "scene format version {found} is not supported, expected at most {supported}"
+            ),
+            SerializationError::UnknownComponent(name) => write!(f, "unknown component `{name}`"),
+            SerializationError::UnknownPrefab(name) => write!(f, "unknown prefab `{name}`"),
+            SerializationError::UnregisteredPrefab(id) => {
+                write!(f, "the prefab of instance {id} is not registered")
+            }
+            SerializationError::InvalidComponent { name, message } => {
+                write!(f, "invalid component `{name}`: {message}")
+            }
+            SerializationError::Scene(error) => write!(f, "cannot build scene: {error}"),
+        }
+    }
+}
+
+impl Error for SerializationError {}
+
+impl From<SceneError> for SerializationError {
+    fn from(error: SceneError) -> Self {
+        SerializationError::Scene(error)
+    }
+}
+
+/// The name under which transforms are saved and overridden, next to the
+/// registered components.
+pub const TRANSFORM_COMPONENT: &str = "Transform";
+
+type SaveFn = fn(&Scene, GameObjectId) -> Option<Box<dyn erased_serde::Serialize>>;
+type LoadFn = fn(&mut dyn erased_serde::Deserializer) -> Result<Value, erased_serde::Error>;
+pub(crate) type PreparedComponent = Box<dyn FnOnce(&Scene, GameObjectId) -> Result<(), SceneError>>;
+type PrepareFn = fn(&str, Value) -> Result<PreparedComponent, SerializationError>;
+type CustomizeFn<'a> =
+    dyn FnMut(usize, &mut BTreeMap<String, Value>) -> Result<(), SerializationError> + 'a;
+
+/// Loaded components are kept as JSON values, which can hold any serde data
+/// losslessly, so that prefabs can merge fields before building the typed
+/// component.
+#[derive(Clone, Copy)]
+struct ComponentEntry {
+    save: SaveFn,
+    load: LoadFn,
+    prepare: PrepareFn,
+}
+
+fn from_value<T: DeserializeOwned>(name: &str, value: Value) -> Result<T, SerializationError> {
+    serde_json::from_value(value).map_err(|error| SerializationError::InvalidComponent {
+        name: name.to_owned(),
+        message: error.to_string(),
+    })
+}
+
+/// The component types that are saved along with scenes, under a stable name
+/// chosen by the game. Transforms are always saved; behaviours never are.
+/// Prefab instances are saved with the name of their prefab, which must be
+/// registered too.
+#[derive(Clone, Default)]
+pub struct ComponentRegistry {
+    entries: BTreeMap<String, ComponentEntry>,
+    prefabs: BTreeMap<String, Arc<Prefab>>,
+}
+
+thread_local! {
+    // The registry used by the `Deserialize` impl of `LoadedComponents`,
+    // set for the duration of `ComponentRegistry::load`.
+    static LOADING_REGISTRY: RefCell<Option<ComponentRegistry>> = const { RefCell::new(None) };
+    // The name of the component that made `LoadedComponents` fail, reported
+    // as `SerializationError::UnknownComponent` instead of a parse error.
+    static UNKNOWN_COMPONENT: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+impl ComponentRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Panics if `name` is already registered.
+    pub fn register<T>(&mut self, name: &str) -> &mut Self
+    where
+        T: Component + Clone + Serialize + DeserializeOwned,
+    {
+        let entry = ComponentEntry {
+            save: |scene, id| {
+                let component = scene.get_component::<T>(id)?.clone();
+                Some(Box::new(component))
+            },
+            load: |deserializer| {
+                let component: T = erased_serde::deserialize(deserializer)?;
+                serde_json::to_value(component).map_err(serde::de::Error::custom)
+            },
+            prepare: |name, value| {
+                let component: T = from_value(name, value)?;
+                Ok(Box::new(move |scene, id| {
+                    scene.add_component(id, component).map(|_| ())
+                }))
+            },
+        };
+        if self.entries.insert(name.to_owned(), entry).is_some() {
+            panic!("component `{name}` is registered twice");
+        }
+
+        self
+    }
+
+    /// Panics if `name` is already registered.
+    pub fn register_prefab(&mut self, name: &str, prefab: &Arc<Prefab>) -> &mut Self {
+        if self
+            .prefabs
+            .insert(name.to_owned(), prefab.clone())
+            .is_some()
+        {
+            panic!("prefab `{name}` is registered twice");
+        }
+
+        self
+    }
+
+    pub fn prefab(&self, name: &str) -> Option<&Arc<Prefab>> {
+        self.prefabs.get(name)
+    }
+
+    pub(crate) fn prefab_name(&self, prefab: &Arc<Prefab>) -> Option<&str> {
+        self.prefabs
+            .iter()
+            .find(|(_, registered)| Arc::ptr_eq(registered, prefab))
+            .map(|(name, _)| name.as_str())
+    }
+
+    pub fn is_registered(&self, name: &str) -> bool {
+        name == TRANSFORM_COMPONENT || self.entries.contains_key(name)
+    }
+
+    /// Checks that `value` is a valid component saved under `name`, and
+    /// returns a function adding or replacing it.
+    pub(crate) fn prepare(
+        &self,
+        name: &str,
+        value: Value,
+    ) -> Result<PreparedComponent, SerializationError> {
+        if name == TRANSFORM_COMPONENT {
+            let transform: Transform = from_value::<TransformData>(name, value)?.into();
+            return Ok(Box::new(move |scene, id| {
+                scene.add_component(id, transform).map(|_| ())
+            }));
+        }
+
+        let entry = self
+            .entries
+            .get(name)
+            .ok_or_else(|| SerializationError::UnknownComponent(name.to_owned()))?;
+        (entry.prepare)(name, value)
+    }
+
+    /// The current value of the component of a game object saved under
+    /// `name`, if it has one.
+    pub(crate) fn component_value(
+        &self,
+        scene: &Scene,
+        id: GameObjectId,
+        name: &str,
+    ) -> Result<Option<Value>, SerializationError> {
+        let invalid = |error: serde_json::Error| SerializationError::InvalidComponent {
+            name: name.to_owned(),
+            message: error.to_string(),
+        };
+        if name == TRANSFORM_COMPONENT {
+            return scene
+                .get_component::<Transform>(id)
+                .map(|transform| serde_json::to_value(TransformData::from(transform.clone())))
+                .transpose()
+                .map_err(invalid);
+        }
+
+        let entry = self
+            .entries
+            .get(name)
+            .ok_or_else(|| SerializationError::UnknownComponent(name.to_owned()))?;
+        (entry.save)(scene, id)
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(invalid)
+    }
+
+    /// Saves the whole scene as RON.
+    pub fn save(&self, scene: &Scene) -> Result<String, SerializationError> {
+        let document = SavedScene {
+            version: SCENE_FORMAT_VERSION,
+            game_objects: scene
+                .roots()
+                .into_iter()
+                .map(|id| SavedGameObject::new(self, scene, id))
+                .collect::<Result<_, _>>()?,
+        };
+
+        ron::ser::to_string_pretty(&document, PrettyConfig::default())
+            .map_err(SerializationError::Serialize)
+    }
+
+    /// Saves one game object and its descendants, in the format of the
+    /// objects of a saved scene.
+    pub fn save_game_object(
+        &self,
+        scene: &Scene,
+        id: GameObjectId,
+    ) -> Result<String, SerializationError> {
+        ron::ser::to_string_pretty(
+            &SavedGameObject::new(self, scene, id)?,
+            PrettyConfig::default(),
+        )
+        .map_err(SerializationError::Serialize)
+    }
+
+    /// Adds the game objects of a saved scene to `scene`, returning the ids
+    /// of the added roots.
+    pub fn load(&self, scene: &Scene, text: &str) -> Result<Vec<GameObjectId>, SerializationError> {
+        // Newer versions may add fields, which the strict parse below rejects.
+        let version: SceneVersion = self.parse(text)?;
+        if version.version > SCENE_FORMAT_VERSION {
+            return Err(SerializationError::UnsupportedVersion {
+                found: version.version,
+                supported: SCENE_FORMAT_VERSION,
+            });
+        }
+
+        let document: LoadedScene = self.parse(text)?;
+        let templates: Vec<GameObjectTemplate> = document
+            .game_objects
+            .into_iter()
+            .map(GameObjectTemplate::from)
+            .collect();
+        // Nothing is added to the scene unless every root is valid.
+        let prepared = templates
+            .iter()
+            .map(|template| template.prepare(self, &mut |_, _| Ok(())))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        templates
+            .iter()
+            .zip(prepared)
+            .map(|(template, prepared)| template.build_prepared(scene, None, prepared))
+            .collect()
+    }
+
+    pub(crate) fn parse<T: DeserializeOwned>(&self, text: &str) -> Result<T, SerializationError> {
+        let previous = LOADING_REGISTRY.replace(Some(self.clone()));
+        UNKNOWN_COMPONENT.set(None);
+        let result = ron::from_str::<T>(text);
+        LOADING_REGISTRY.set(previous);
+
+        result.map_err(|error| match UNKNOWN_COMPONENT.take() {
+            Some(name) => SerializationError::UnknownComponent(name),
+            None => SerializationError::Parse(error),
+        })
+    }
+
+    /// Loads a saved scene into a new [`Scene`].
+    pub fn load_scene(&self, text: &str) -> Result<Arc<Scene>, SerializationError> {
+        let scene = Scene::new();
+        self.load(&scene, text)?;
+
+        Ok(scene)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Transform", deny_unknown_fields)]
+struct TransformData {
+    position: (f64, f64, f64),
+    rotation: (f64, f64, f64),
+    scale: (f64, f64, f64),
+}
+
+impl Default for TransformData {
+    fn default() -> Self {
+        Transform::default().into()
+    }
+}
+
+impl From<Transform> for TransformData {
+    fn from(transform: Transform) -> Self {
+        let tuple = |v: Vector3f| (v.x, v.y, v.z);
+        Self {
+            position: tuple(transform.position()),
+            rotation: tuple(transform.rotation()),
+            scale: tuple(transform.scale()),
+        }
+    }
+}
+
+impl From<TransformData> for Transform {
+    fn from(data: TransformData) -> Self {
+        let vector = |(x, y, z)| Vector3f::new(x, y, z);
+        Transform::new(
+            vector(data.position),
+            vector(data.rotation),
+            vector(data.scale),
+        )
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename = "Scene")]
+struct SavedScene {
+    version: u32,
+    game_objects: Vec<SavedGameObject>,
+}
+
+#[derive(Serialize)]
+#[serde(rename = "GameObject")]
+struct SavedGameObject {
+    name: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    enabled: bool,
+    transform: TransformData,
+    #[serde(skip_serializing_if = "SavedComponents::is_empty")]
+    components: SavedComponents,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prefab: Option<PrefabLink>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    children: Vec<SavedGameObject>,
+}
+
+impl SavedGameObject {
+    fn new(
+        registry: &ComponentRegistry,
+        scene: &Scene,
+        id: GameObjectId,
+    ) -> Result<Self, SerializationError> {
+        let game_object = scene
+            .game_object(id)
+            .ok_or(SceneError::InvalidGameObject(id))?;
+        Ok(Self {
+            name: game_object.name(),
+            tags: game_object.tags(),
+            enabled: game_object.is_enabled(),
+            transform: game_object
+                .get_component::<Transform>()
+                .map(|transform| transform.clone())
+                .unwrap_or_default()
+                .into(),
+            components: SavedComponents(
+                registry
+                    .entries
+                    .iter()
+                    .filter_map(|(name, entry)| Some((name.clone(), (entry.save)(scene, id)?)))
+                    .collect(),
+            ),
+            prefab: PrefabLink::new(registry, scene, id)?,
+            children: game_object
+                .children()
+                .into_iter()
+                .map(|child| SavedGameObject::new(registry, scene, child))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+struct SavedComponents(Vec<(String, Box<dyn erased_serde::Serialize>)>);
+
+impl SavedComponents {
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Serialize for SavedComponents {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (name, component) in &self.0 {
+            map.serialize_entry(name, component)?;
+        }
+        map.end()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Scene")]
+struct SceneVersion {
+    version: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Scene")]
+struct LoadedScene {
+    #[serde(default)]
+    game_objects: Vec<LoadedGameObject>,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "GameObject", deny_unknown_fields)]
+pub(crate) struct LoadedGameObject {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default = "enabled_by_default")]
+    enabled: bool,
+    #[serde(default)]
+    transform: TransformData,
+    #[serde(default)]
+    components: LoadedComponents,
+    #[serde(default)]
+    prefab: Option<PrefabLink>,
+    #[serde(default)]
+    children: Vec<LoadedGameObject>,
+}
+
+/// A game object and its descendants with their components as values, as
+/// loaded from a scene or a prefab.
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct GameObjectTemplate {
+    pub(crate) name: String,
+    pub(crate) tags: Vec<String>,
+    pub(crate) enabled: bool,
+    /// Including the transform, under [`TRANSFORM_COMPONENT`].
+    pub(crate) components: BTreeMap<String, Value>,
+    pub(crate) prefab: Option<PrefabLink>,
+    pub(crate) children: Vec<GameObjectTemplate>,
+}
+
+impl From<LoadedGameObject> for GameObjectTemplate {
+    fn from(game_object: LoadedGameObject) -> Self {
+        let mut components: BTreeMap<String, Value> =
+            game_object.components.0.into_iter().collect();
+        components.insert(
+            TRANSFORM_COMPONENT.to_owned(),
+            serde_json::to_value(game_object.transform).unwrap(),
+        );
+
+        Self {
+            name: game_object.name,
+            tags: game_object.tags,
+            enabled: game_object.enabled,
+            components,
+            prefab: game_object.prefab,
+            children: game_object
+                .children
+                .into_iter()
+                .map(GameObjectTemplate::from)
+                .collect(),
+        }
+    }
+}
+
+struct PreparedNode {
+    components: Vec<PreparedComponent>,
+    prefab: Option<PreparedComponent>,
+}
+
+impl GameObjectTemplate {
+    /// Adds the game objects to `scene`, letting `customize` change the
+    /// components of every game object, which it gets along with its index
+    /// in pre-order. Nothing is added to the scene if a component is
+    /// invalid.
+    pub(crate) fn build_with(
+        &self,
+        registry: &ComponentRegistry,
+        scene: &Scene,
+        parent: Option<GameObjectId>,
+        customize: &mut CustomizeFn,
+    ) -> Result<GameObjectId, SerializationError> {
+        let prepared = self.prepare(registry, customize)?;
+        self.build_prepared(scene, parent, prepared)
+    }
+
+    /// Checks the components of every game object, in pre-order.
+    fn prepare(
+        &self,
+        registry: &ComponentRegistry,
+        customize: &mut CustomizeFn,
+    ) -> Result<Vec<PreparedNode>, SerializationError> {
+        let mut prepared = Vec::new();
+        for (index, node) in self.nodes().into_iter().enumerate() {
+            let mut components = node.components.clone();
+            customize(index, &mut components)?;
+            prepared.push(PreparedNode {
+                components: components
+                    .into_iter()
+                    .map(|(name, value)| registry.prepare(&name, value))
+                    .collect::<Result<_, _>>()?,
+                prefab: node
+                    .prefab
+                    .as_ref()
+                    .map(|link| link.prepare(registry))
+                    .transpose()?,
+            });
+        }
+
+        Ok(prepared)
+    }
+
+    fn build_prepared(
+        &self,
+        scene: &Scene,
+        parent: Option<GameObjectId>,
+        prepared: Vec<PreparedNode>,
+    ) -> Result<GameObjectId, SerializationError> {
+        self.build_node(scene, parent, &mut prepared.into_iter())
+    }
+
+    fn build_node(
+        &self,
+        scene: &Scene,
+        parent: Option<GameObjectId>,
+        prepared: &mut impl Iterator<Item = PreparedNode>,
+    ) -> Result<GameObjectId, SerializationError> {
+        let id = scene.add_game_object();
+        if let Some(parent) = parent {
+            scene.attach(id, parent)?;
+        }
+
+        let game_object = scene
+            .game_object(id)
+            .ok_or(SceneError::InvalidGameObject(id))?;
+        game_object.set_name(self.name.clone());
+        for tag in &self.tags {
+            game_object.add_tag(tag.clone());
+        }
+        let node = prepared.next().unwrap();
+        for component in node.components {
+            component(scene, id)?;
+        }
+
+        for child in &self.children {
+            child.build_node(scene, Some(id), prepared)?;
+        }
+        // After the children, which belong to the prefab instance.
+        if let Some(prefab) = node.prefab {
+            prefab(scene, id)?;
+        }
+        // After the children, so that they are deactivated along with it.
+        scene.set_enabled(id, self.enabled)?;
+
+        Ok(id)
+    }
+
+    /// Pre-order, matching the indices passed to `build_with`.
+    pub(crate) fn nodes(&self) -> Vec<&GameObjectTemplate> {
+        let mut nodes = vec![self];
+        for child in &self.children {
+            nodes.extend(child.nodes());
+        }
+
+        nodes
+    }
+
+    /// The node at `index` in pre-order.
+    pub(crate) fn node_mut(&mut self, index: usize) -> Option<&mut GameObjectTemplate> {
+        fn find<'a>(
+            node: &'a mut GameObjectTemplate,
+            index: &mut usize,
+        ) -> Option<&'a mut GameObjectTemplate> {
+            if *index == 0 {
+                return Some(node);
+            }
+            *index -= 1;
+            for child in &mut node.children {
+                if let Some(found) = find(child, index) {
+                    return Some(found);
+                }
+            }
+
+            None
+        }
+
+        find(self, &mut { index })
+    }
+}
+
+#[derive(Default)]
+struct LoadedComponents(Vec<(String, Value)>);
+
+impl<'de> Deserialize<'de> for LoadedComponents {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(LoadedComponentsVisitor)
+    }
+}
+
+struct LoadedComponentsVisitor;
+
+impl<'de> Visitor<'de> for LoadedComponentsVisitor {
+    type Value = LoadedComponents;
+
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "a map from component names to components")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        use serde::de::Error;
+
+        let mut components = Vec::new();
+        while let Some(name) = map.next_key::<String>()? {
+            let entry = LOADING_REGISTRY.with_borrow(|registry| {
+                registry
+                    .as_ref()
+                    .and_then(|registry| registry.entries.get(&name).copied())
+            });
+            let Some(entry) = entry else {
+                let error = A::Error::custom(format!("unknown component `{name}`"));
+                UNKNOWN_COMPONENT.set(Some(name));
+                return Err(error);
+            };
+            components.push((name, map.next_value_seed(ComponentSeed(entry))?));
+        }
+
+        Ok(LoadedComponents(components))
+    }
+}
+
+struct ComponentSeed(ComponentEntry);
+
+impl<'de> serde::de::DeserializeSeed<'de> for ComponentSeed {
+    type Value = Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        use serde::de::Error;
+
+        let mut deserializer = <dyn erased_serde::Deserializer>::erase(deserializer);
+        (self.0.load)(&mut deserializer).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde::Serialize;
+    use util::math::vector::Vector3f;
+
+    use super::ComponentRegistry;
+    use super::SerializationError;
+    use crate::scene::Scene;
+    use crate::scene::SceneError;
+    use crate::transform::Transform;
+
+    #[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+    enum Team {
+        Red,
+        Blue,
+    }
+
+    #[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+    struct Health {
+        current: u32,
+        max: u32,
+    }
+
+    fn registry() -> ComponentRegistry {
+        let mut registry = ComponentRegistry::new();
+        registry
+            .register::<Team>("Team")
+            .register::<Health>("Health");
+        registry
+    }
+
+    #[test]
+    fn test_scene_round_trip() {
+        let scene = Scene::new();
+        let player = scene.game_object(scene.add_game_object()).unwrap();
+        let weapon = scene.game_object(scene.add_game_object()).unwrap();
+        scene.attach(weapon.id(), player.id()).unwrap();
+        player.set_name("player");
+        player.add_tag("friendly");
+        player.add_component(Team::Blue);
+        player.add_component(Health {
+            current: 7,
+            max: 10,
+        });
+        player.update_transform(|t| t.set_position(Vector3f::new(1.0, 2.0, 3.0)));
+        weapon.set_name("weapon");
+        weapon.set_enabled(false);
+
+        let registry = registry();
+        let text = registry.save(&scene).unwrap();
+        let loaded = registry.load_scene(&text).unwrap();
+
+        let player = loaded.find_by_name("player").unwrap();
+        let weapon = loaded.find_by_name("weapon").unwrap();
+        assert_eq!(Some(player), loaded.parent(weapon));
+        assert!(!loaded.game_object(weapon).unwrap().is_enabled());
+        assert_eq!(Team::Blue, *loaded.get_component::<Team>(player).unwrap());
+        assert_eq!(7, loaded.get_component::<Health>(player).unwrap().current);
+        assert_eq!(
+            Vector3f::new(1.0, 2.0, 3.0),
+            loaded
+                .get_component::<Transform>(player)
+                .unwrap()
+                .position()
+        );
+        assert_eq!(text, registry.save(&loaded).unwrap());
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let registry = registry();
+        let scene = Scene::new();
+
+        assert!(matches!(
+            registry.load(
+                &scene,
+                "(version: 1, game_objects: [(components: {\"Mana\": 3})])",
+            ),
+            Err(SerializationError::UnknownComponent(name)) if name == "Mana"
+        ));
+        assert!(matches!(
+            registry.load(&scene, "(version: 1, game_objects: [(hat: true)])"),
+            Err(SerializationError::Parse(_))
+        ));
+
+        assert!(matches!(
+            registry.load(
+                &scene,
+                "(version: 99, game_objects: [(name: \"a\", hat: true)])"
+            ),
+            Err(SerializationError::UnsupportedVersion {
+                found: 99,
+                supported: 1
+            })
+        ));
+
+        assert!(matches!(
+            registry.load(
+                &scene,
+                "(version: 1, game_objects: [(name: \"a\"), (prefab: Some((prefab: \"b\", \
+                 game_objects: [Some(0)])))])"
+            ),
+            Err(SerializationError::UnknownPrefab(name)) if name == "b"
+        ));
+        assert!(scene.game_objects().is_empty());
+
+        let removed = scene.add_game_object();
+        scene.destroy(removed).unwrap();
+        scene.remove_destroyed();
+        assert!(matches!(
+            registry.save_game_object(&scene, removed),
+            Err(SerializationError::Scene(SceneError::InvalidGameObject(id))) if id == removed
+        ));
+    }
+}
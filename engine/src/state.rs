@@ -0,0 +1,262 @@
+use std::any::Any;
+use std::any::TypeId;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::component::Behaviour;
+use crate::query::ComponentAccess;
+use crate::scene::GameObject;
+use crate::system::System;
+use crate::EngineContext;
+
+/// An app state such as a main menu, a level being played or a pause menu.
+/// States form a stack; only the top one is current.
+#[allow(unused_variables)]
+pub trait GameState: Any + Send {
+    /// When the state is pushed or replaces the current one.
+    fn on_enter(&mut self, engine_context: &EngineContext) {}
+
+    /// When the state is popped or replaced.
+    fn on_exit(&mut self, engine_context: &EngineContext) {}
+
+    /// When another state is pushed on top of this one.
+    fn on_pause(&mut self, engine_context: &EngineContext) {}
+
+    /// When the state on top of this one is popped.
+    fn on_resume(&mut self, engine_context: &EngineContext) {}
+}
+
+type SharedState = Arc<Mutex<dyn GameState>>;
+
+enum Transition {
+    Push(TypeId, SharedState),
+    Pop,
+    Replace(TypeId, SharedState),
+}
+
+/// The state stack. Transitions are requested from anywhere and applied by
+/// the engine at the start of the next frame, in the order they were
+/// requested.
+pub struct GameStates {
+    stack: Mutex<Vec<(TypeId, SharedState)>>,
+    transitions: Mutex<Vec<Transition>>,
+}
+
+impl GameStates {
+    pub(crate) fn new() -> Self {
+        Self {
+            stack: Mutex::new(Vec::new()),
+            transitions: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn push<S: GameState>(&self, state: S) {
+        self.transitions.lock().unwrap().push(Transition::Push(
+            TypeId::of::<S>(),
+            Arc::new(Mutex::new(state)),
+        ));
+    }
+
+    /// Does nothing if the stack is empty.
+    pub fn pop(&self) {
+        self.transitions.lock().unwrap().push(Transition::Pop);
+    }
+
+    /// Replaces the current state, or pushes `state` if the stack is empty.
+    pub fn replace<S: GameState>(&self, state: S) {
+        self.transitions.lock().unwrap().push(Transition::Replace(
+            TypeId::of::<S>(),
+            Arc::new(Mutex::new(state)),
+        ));
+    }
+
+    pub fn is_current<S: GameState>(&self) -> bool {
+        self.stack
+            .lock()
+            .unwrap()
+            .last()
+            .is_some_and(|(type_id, _)| *type_id == TypeId::of::<S>())
+    }
+
+    /// Whether a state of type `S` is anywhere on the stack.
+    pub fn contains<S: GameState>(&self) -> bool {
+        self.stack
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|(type_id, _)| *type_id == TypeId::of::<S>())
+    }
+
+    pub fn depth(&self) -> usize {
+        self.stack.lock().unwrap().len()
+    }
+
+    /// Runs `fun` on the topmost state of type `S`. Returns `None` if there
+    /// is none or if it is running a hook.
+    pub fn with_state<S: GameState, R, F: FnOnce(&mut S) -> R>(&self, fun: F) -> Option<R> {
+        let state = self
+            .stack
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .find(|(type_id, _)| *type_id == TypeId::of::<S>())
+            .map(|(_, state)| state.clone())?;
+        let mut state = state.try_lock().ok()?;
+        let state: &mut dyn Any = &mut *state;
+
+        state.downcast_mut().map(fun)
+    }
+
+    /// Applies the requested transitions, including the ones requested by
+    /// the hooks they call. Only call it when no jobs are running.
+    pub(crate) fn apply(&self, engine_context: &EngineContext) {
+        loop {
+            let transitions = std::mem::take(&mut *self.transitions.lock().unwrap());
+            if transitions.is_empty() {
+                break;
+            }
+
+            for transition in transitions {
+                // Hooks run without the stack locked, so they can query it.
+                let mut stack = self.stack.lock().unwrap();
+                match transition {
+                    Transition::Push(type_id, state) => {
+                        let below = stack.last().map(|(_, state)| state.clone());
+                        stack.push((type_id, state.clone()));
+                        drop(stack);
+                        if let Some(below) = below {
+                            below.lock().unwrap().on_pause(engine_context);
+                        }
+                        state.lock().unwrap().on_enter(engine_context);
+                    }
+                    Transition::Pop => {
+                        let Some((_, popped)) = stack.pop() else {
+                            continue;
+                        };
+                        let below = stack.last().map(|(_, state)| state.clone());
+                        drop(stack);
+                        popped.lock().unwrap().on_exit(engine_context);
+                        if let Some(below) = below {
+                            below.lock().unwrap().on_resume(engine_context);
+                        }
+                    }
+                    Transition::Replace(type_id, state) => {
+                        let replaced = stack.pop();
+                        stack.push((type_id, state.clone()));
+                        drop(stack);
+                        if let Some((_, replaced)) = replaced {
+                            replaced.lock().unwrap().on_exit(engine_context);
+                        }
+                        state.lock().unwrap().on_enter(engine_context);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A [`System`] only running while `S` is the current state; see
+/// [`Engine::add_system_in_state`](crate::Engine::add_system_in_state).
+pub struct InStateSystem<S> {
+    system: Box<dyn System>,
+    _state: PhantomData<fn() -> S>,
+}
+
+impl<S: GameState> InStateSystem<S> {
+    pub fn new<T: System>(system: T) -> Self {
+        Self {
+            system: Box::new(system),
+            _state: PhantomData,
+        }
+    }
+}
+
+impl<S: GameState> System for InStateSystem<S> {
+    fn access(&self) -> Vec<ComponentAccess> {
+        self.system.access()
+    }
+
+    fn run(&self, engine_context: &EngineContext) {
+        if engine_context.states().is_current::<S>() {
+            self.system.run(engine_context);
+        }
+    }
+}
+
+/// Wraps a [`Behaviour`] so that its update hooks only run while `S` is the
+/// current state. The other hooks always run. Look the behaviour up with
+/// [`GameObject::with_behaviour`] as `InState<S, B>`.
+pub struct InState<S, B> {
+    behaviour: B,
+    _state: PhantomData<fn() -> S>,
+}
+
+impl<S: GameState, B: Behaviour> InState<S, B> {
+    pub fn new(behaviour: B) -> Self {
+        Self {
+            behaviour,
+            _state: PhantomData,
+        }
+    }
+
+    pub fn behaviour(&mut self) -> &mut B {
+        &mut self.behaviour
+    }
+}
+
+impl<S: GameState, B: Behaviour> Behaviour for InState<S, B> {
+    fn on_start(&mut self, engine_context: &EngineContext, game_object: &GameObject) {
+        self.behaviour.on_start(engine_context, game_object);
+    }
+
+    fn on_enable(&mut self, engine_context: &EngineContext, game_object: &GameObject) {
+        self.behaviour.on_enable(engine_context, game_object);
+    }
+
+    fn on_disable(&mut self, engine_context: &EngineContext, game_object: &GameObject) {
+        self.behaviour.on_disable(engine_context, game_object);
+    }
+
+    fn on_fixed_update(
+        &mut self,
+        engine_context: &EngineContext,
+        game_object: &GameObject,
+        fixed_delta_time: Duration,
+    ) {
+        if engine_context.states().is_current::<S>() {
+            self.behaviour
+                .on_fixed_update(engine_context, game_object, fixed_delta_time);
+        }
+    }
+
+    fn on_update(
+        &mut self,
+        engine_context: &EngineContext,
+        game_object: &GameObject,
+        delta_time: Duration,
+    ) {
+        if engine_context.states().is_current::<S>() {
+            self.behaviour
+                .on_update(engine_context, game_object, delta_time);
+        }
+    }
+
+    fn on_late_update(
+        &mut self,
+        engine_context: &EngineContext,
+        game_object: &GameObject,
+        delta_time: Duration,
+    ) {
+        if engine_context.states().is_current::<S>() {
+            self.behaviour
+                .on_late_update(engine_context, game_object, delta_time);
+        }
+    }
+
+    fn on_destroy(&mut self, engine_context: &EngineContext, game_object: &GameObject) {
+        self.behaviour.on_destroy(engine_context, game_object);
+    }
+}
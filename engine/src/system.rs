@@ -0,0 +1,343 @@
+use std::marker::PhantomData;
+
+use util::enum_map::EnumMap;
+use util::smart_enum;
+
+use crate::query::ComponentAccess;
+use crate::query::QueryData;
+use crate::scene::GameObjectId;
+use crate::scene::Scene;
+use crate::EngineContext;
+use crate::EngineThreadCategory;
+
+/// Logic running over the components of a whole scene each update. Systems
+/// whose [`access`](System::access) sets don't conflict run in parallel.
+pub trait System: Send + Sync + 'static {
+    fn access(&self) -> Vec<ComponentAccess>;
+    fn run(&self, engine_context: &EngineContext);
+}
+
+/// A [`System`] calling `fun` for every active game object matching `Q`, in
+/// every loaded scene. `fun` gets the scene of the game object, which its
+/// commands are applied to.
+pub struct QuerySystem<Q, F> {
+    fun: F,
+    _query: PhantomData<fn() -> Q>,
+}
+
+impl<Q, F> QuerySystem<Q, F>
+where
+    Q: QueryData + 'static,
+    F: Fn(&EngineContext, &Scene, GameObjectId, Q::Item<'_>) + Send + Sync + 'static,
+{
+    pub fn new(fun: F) -> Self {
+        Self {
+            fun,
+            _query: PhantomData,
+        }
+    }
+}
+
+impl<Q, F> System for QuerySystem<Q, F>
+where
+    Q: QueryData + 'static,
+    F: Fn(&EngineContext, &Scene, GameObjectId, Q::Item<'_>) + Send + Sync + 'static,
+{
+    fn access(&self) -> Vec<ComponentAccess> {
+        Q::access()
+    }
+
+    fn run(&self, engine_context: &EngineContext) {
+        for scene in engine_context.scenes() {
+            let inactive = scene.inactive_game_objects();
+            engine_context.commands().with_scene(&scene, || {
+                scene.query::<Q>().for_each(|id, item| {
+                    if !inactive.contains(&id) {
+                        (self.fun)(engine_context, &scene, id, item);
+                    }
+                });
+            });
+        }
+    }
+}
+
+/// A [`System`] running `fun` once per update, e.g. for logic over the whole
+/// game like a camera follow or an AI director. `access` lists the
+/// components `fun` reads and writes, for scheduling it.
+pub struct FnSystem<F> {
+    access: Vec<ComponentAccess>,
+    fun: F,
+}
+
+impl<F: Fn(&EngineContext) + Send + Sync + 'static> FnSystem<F> {
+    pub fn new(access: Vec<ComponentAccess>, fun: F) -> Self {
+        Self { access, fun }
+    }
+}
+
+impl<F: Fn(&EngineContext) + Send + Sync + 'static> System for FnSystem<F> {
+    fn access(&self) -> Vec<ComponentAccess> {
+        self.access.clone()
+    }
+
+    fn run(&self, engine_context: &EngineContext) {
+        (self.fun)(engine_context);
+    }
+}
+
+smart_enum!(
+    pub,
+    Stage,
+    FixedUpdate,
+    PreUpdate,
+    Update,
+    PostUpdate,
+    RenderPrep
+);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct SystemId(usize);
+
+struct ScheduledSystem {
+    system: Box<dyn System>,
+    access: Vec<ComponentAccess>,
+    stage: Stage,
+    /// The systems of the same stage that must run first.
+    after: Vec<usize>,
+}
+
+/// Systems grouped by [`Stage`]. Within a stage, systems run in
+/// registration order unless ordering constraints say otherwise, grouped
+/// into batches of consecutive systems that don't conflict with each other.
+/// Batches run one after another, so a system always sees the writes of
+/// every earlier system it conflicts with.
+pub struct Systems {
+    systems: Vec<ScheduledSystem>,
+    batches: EnumMap<Stage, Vec<Vec<usize>>>,
+}
+
+impl Systems {
+    pub fn new() -> Self {
+        Self {
+            systems: Vec::new(),
+            batches: EnumMap::new(|_| Vec::new()),
+        }
+    }
+
+    /// Adds `system` to [`Stage::Update`].
+    pub fn add<S: System>(&mut self, system: S) -> SystemId {
+        self.add_to(Stage::Update, system)
+    }
+
+    pub fn add_to<S: System>(&mut self, stage: Stage, system: S) -> SystemId {
+        self.systems.push(ScheduledSystem {
+            access: system.access(),
+            system: Box::new(system),
+            stage,
+            after: Vec::new(),
+        });
+        self.schedule(stage);
+
+        SystemId(self.systems.len() - 1)
+    }
+
+    /// Makes `first` run before `then`. Panics if that contradicts the
+    /// stages of the systems or other constraints, leaving the systems
+    /// unchanged.
+    pub fn order(&mut self, first: SystemId, then: SystemId) {
+        let (first_stage, then_stage) = (self.systems[first.0].stage, self.systems[then.0].stage);
+        assert!(
+            first_stage <= then_stage,
+            "a system of {first_stage} can't run after one of {then_stage}"
+        );
+        if first_stage == then_stage {
+            self.systems[then.0].after.push(first.0);
+            if self.sorted(then_stage).is_none() {
+                self.systems[then.0].after.pop();
+                panic!("the systems of {then_stage} would have cyclic constraints");
+            }
+            self.schedule(then_stage);
+        }
+    }
+
+    /// The systems of `stage` sorted by their constraints, keeping
+    /// registration order where possible, or `None` if the constraints are
+    /// cyclic.
+    fn sorted(&self, stage: Stage) -> Option<Vec<usize>> {
+        let mut pending: Vec<usize> = (0..self.systems.len())
+            .filter(|i| self.systems[*i].stage == stage)
+            .collect();
+        let mut order: Vec<usize> = Vec::new();
+        while !pending.is_empty() {
+            let next = pending.iter().position(|i| {
+                self.systems[*i]
+                    .after
+                    .iter()
+                    .all(|first| order.contains(first))
+            })?;
+            order.push(pending.remove(next));
+        }
+
+        Some(order)
+    }
+
+    /// Sorts and batches the systems of `stage`.
+    fn schedule(&mut self, stage: Stage) {
+        // Constraints are only added by `order`, which rejects cycles.
+        let order = self.sorted(stage).unwrap();
+        let mut batches: Vec<Vec<usize>> = Vec::new();
+        for i in order {
+            let system = &self.systems[i];
+            let fits_last_batch = batches.last().is_some_and(|batch| {
+                batch.iter().all(|other| {
+                    let other = &self.systems[*other];
+                    system
+                        .access
+                        .iter()
+                        .all(|a| other.access.iter().all(|b| !a.conflicts_with(b)))
+                }) && !system.after.iter().any(|first| batch.contains(first))
+            });
+
+            if fits_last_batch {
+                batches.last_mut().unwrap().push(i);
+            } else {
+                batches.push(vec![i]);
+            }
+        }
+        *self.batches.get_mut(stage) = batches;
+    }
+
+    pub fn len(&self) -> usize {
+        self.systems.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.systems.is_empty()
+    }
+
+    /// The number of batches over all stages.
+    pub fn batch_count(&self) -> usize {
+        self.batches.values().map(Vec::len).sum()
+    }
+
+    /// Runs the systems of `stage`, applying the commands they recorded after
+    /// every batch.
+    pub fn run(&self, stage: Stage, engine_context: &EngineContext) {
+        let commands = engine_context.commands();
+        for batch in self.batches.get(stage) {
+            let first_source = commands.reserve_sources(batch.len());
+            engine_context.scheduler().scoped(|s| {
+                for (i, index) in batch.iter().enumerate() {
+                    let scheduled = &self.systems[*index];
+                    s.schedule_job(EngineThreadCategory::GameObject, move || {
+                        commands.with_source(first_source + i as u64, || {
+                            scheduled.system.run(engine_context);
+                        });
+                    });
+                }
+            });
+            engine_context.sync();
+        }
+    }
+}
+
+impl Default for Systems {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+    use std::panic::AssertUnwindSafe;
+
+    use super::Stage;
+    use super::System;
+    use super::Systems;
+    use crate::query::ComponentAccess;
+    use crate::EngineContext;
+
+    struct A;
+    struct B;
+
+    struct TestSystem(Vec<ComponentAccess>);
+
+    impl System for TestSystem {
+        fn access(&self) -> Vec<ComponentAccess> {
+            self.0.clone()
+        }
+
+        fn run(&self, _: &EngineContext) {}
+    }
+
+    #[test]
+    fn test_system_batching() {
+        let mut systems = Systems::new();
+        systems.add(TestSystem(vec![ComponentAccess::read::<A>()]));
+        systems.add(TestSystem(vec![
+            ComponentAccess::read::<A>(),
+            ComponentAccess::write::<B>(),
+        ]));
+        assert_eq!(1, systems.batch_count());
+
+        systems.add(TestSystem(vec![ComponentAccess::read::<B>()]));
+        systems.add(TestSystem(vec![ComponentAccess::read::<A>()]));
+        assert_eq!(2, systems.batch_count());
+
+        systems.add(TestSystem(vec![ComponentAccess::write::<A>()]));
+        assert_eq!(3, systems.batch_count());
+        assert_eq!(5, systems.len());
+    }
+
+    #[test]
+    fn test_system_ordering() {
+        let mut systems = Systems::new();
+        let a = systems.add(TestSystem(vec![ComponentAccess::read::<A>()]));
+        systems.add(TestSystem(vec![ComponentAccess::read::<A>()]));
+        let c = systems.add(TestSystem(vec![ComponentAccess::read::<A>()]));
+        assert_eq!(1, systems.batch_count());
+
+        systems.order(c, a);
+        assert_eq!(2, systems.batch_count());
+
+        let render = systems.add_to(Stage::RenderPrep, TestSystem(Vec::new()));
+        systems.order(a, render);
+        assert_eq!(3, systems.batch_count());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_system_ordering_rejects_cycles() {
+        let mut systems = Systems::new();
+        let a = systems.add(TestSystem(Vec::new()));
+        let b = systems.add(TestSystem(Vec::new()));
+        systems.order(a, b);
+        systems.order(b, a);
+    }
+
+    #[test]
+    fn test_system_ordering_recovers_from_cycles() {
+        let mut systems = Systems::new();
+        let a = systems.add(TestSystem(Vec::new()));
+        let b = systems.add(TestSystem(Vec::new()));
+        systems.order(a, b);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| systems.order(b, a)));
+        assert!(result.is_err());
+        assert_eq!(2, systems.batch_count());
+
+        systems.add(TestSystem(Vec::new()));
+        assert_eq!(2, systems.batch_count());
+    }
+
+    #[test]
+    fn test_resource_access() {
+        let mut systems = Systems::new();
+        systems.add(TestSystem(vec![ComponentAccess::write_resource::<A>()]));
+        systems.add(TestSystem(vec![ComponentAccess::write::<A>()]));
+        assert_eq!(1, systems.batch_count());
+
+        systems.add(TestSystem(vec![ComponentAccess::read_resource::<A>()]));
+        assert_eq!(2, systems.batch_count());
+    }
+}
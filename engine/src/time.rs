@@ -0,0 +1,159 @@
+use std::sync::RwLock;
+use std::time::Duration;
+
+use util::enum_map::EnumMap;
+use util::internal_mut_struct;
+use util::smart_enum;
+
+smart_enum!(pub, Subsystem, FixedUpdate, Behaviours, Systems);
+
+struct TimeImpl {
+    elapsed: Duration,
+    unscaled_elapsed: Duration,
+    delta_time: Duration,
+    unscaled_delta_time: Duration,
+    fixed_delta_time: Duration,
+    frame_count: u64,
+    time_scale: f64,
+    paused: bool,
+    paused_subsystems: EnumMap<Subsystem, bool>,
+}
+
+internal_mut_struct!(RwLock; Time, TimeImpl);
+
+/// The game clock. Scaled times follow the time scale and stop while the
+/// game is paused; unscaled times follow the real frame times, e.g. for
+/// animating a pause menu.
+impl Time {
+    pub fn new() -> Self {
+        Self {
+            inner: RwLock::new(TimeImpl {
+                elapsed: Duration::ZERO,
+                unscaled_elapsed: Duration::ZERO,
+                delta_time: Duration::ZERO,
+                unscaled_delta_time: Duration::ZERO,
+                fixed_delta_time: Duration::ZERO,
+                frame_count: 0,
+                time_scale: 1.0,
+                paused: false,
+                paused_subsystems: EnumMap::new(|_| false),
+            }),
+        }
+    }
+
+    /// The scaled time since the first frame.
+    pub fn elapsed(&self) -> Duration {
+        self.read_inner().elapsed
+    }
+
+    pub fn unscaled_elapsed(&self) -> Duration {
+        self.read_inner().unscaled_elapsed
+    }
+
+    /// The scaled duration of the current frame.
+    pub fn delta_time(&self) -> Duration {
+        self.read_inner().delta_time
+    }
+
+    pub fn unscaled_delta_time(&self) -> Duration {
+        self.read_inner().unscaled_delta_time
+    }
+
+    /// The duration of the current or last fixed step.
+    pub fn fixed_delta_time(&self) -> Duration {
+        self.read_inner().fixed_delta_time
+    }
+
+    pub(crate) fn set_fixed_delta_time(&self, fixed_delta_time: Duration) {
+        self.write_inner().fixed_delta_time = fixed_delta_time;
+    }
+
+    /// The number of frames started so far, counting the current one.
+    pub fn frame_count(&self) -> u64 {
+        self.read_inner().frame_count
+    }
+
+    pub fn time_scale(&self) -> f64 {
+        self.read_inner().time_scale
+    }
+
+    /// Panics if `time_scale` is negative or not finite. Takes effect on the
+    /// next frame.
+    pub fn set_time_scale(&self, time_scale: f64) {
+        assert!(
+            time_scale.is_finite() && time_scale >= 0.0,
+            "invalid time scale {time_scale}"
+        );
+        self.write_inner().time_scale = time_scale;
+    }
+
+    /// While paused, scaled time stands still whatever the time scale is.
+    pub fn is_paused(&self) -> bool {
+        self.read_inner().paused
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.write_inner().paused = paused;
+    }
+
+    /// Whether the engine skips `subsystem` entirely, independently of the
+    /// global pause.
+    pub fn is_subsystem_paused(&self, subsystem: Subsystem) -> bool {
+        *self.read_inner().paused_subsystems.get(subsystem)
+    }
+
+    pub fn set_subsystem_paused(&self, subsystem: Subsystem, paused: bool) {
+        *self.write_inner().paused_subsystems.get_mut(subsystem) = paused;
+    }
+
+    /// Starts a frame that lasted `unscaled_delta_time` and returns its scaled
+    /// duration.
+    pub(crate) fn advance(&self, unscaled_delta_time: Duration) -> Duration {
+        let mut inner = self.write_inner();
+        let delta_time = if inner.paused {
+            Duration::ZERO
+        } else {
+            unscaled_delta_time.mul_f64(inner.time_scale)
+        };
+
+        inner.frame_count += 1;
+        inner.unscaled_delta_time = unscaled_delta_time;
+        inner.unscaled_elapsed += unscaled_delta_time;
+        inner.delta_time = delta_time;
+        inner.elapsed += delta_time;
+
+        delta_time
+    }
+}
+
+impl Default for Time {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Time;
+
+    #[test]
+    fn test_time() {
+        let time = Time::new();
+        time.advance(Duration::from_millis(10));
+        time.set_time_scale(0.5);
+        assert_eq!(
+            Duration::from_millis(10),
+            time.advance(Duration::from_millis(20))
+        );
+        time.set_paused(true);
+        assert_eq!(Duration::ZERO, time.advance(Duration::from_millis(30)));
+
+        assert_eq!(3, time.frame_count());
+        assert_eq!(Duration::from_millis(20), time.elapsed());
+        assert_eq!(Duration::from_millis(60), time.unscaled_elapsed());
+        assert_eq!(Duration::ZERO, time.delta_time());
+        assert_eq!(Duration::from_millis(30), time.unscaled_delta_time());
+    }
+}
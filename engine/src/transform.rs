@@ -0,0 +1,95 @@
+use util::math::matrix::Matrix4x4f;
+use util::math::vector::Vector3f;
+
+/// Position, rotation (Euler angles in radians, see
+/// [`Matrix4x4f::rotation`]) and scale relative to the parent game object.
+/// The world matrix is refreshed by [`Scene::update_transforms`](crate::scene::Scene::update_transforms).
+#[derive(Clone, PartialEq, Debug)]
+pub struct Transform {
+    position: Vector3f,
+    rotation: Vector3f,
+    scale: Vector3f,
+    world_matrix: Matrix4x4f,
+    dirty: bool,
+}
+
+impl Transform {
+    pub fn new(position: Vector3f, rotation: Vector3f, scale: Vector3f) -> Self {
+        Self {
+            position,
+            rotation,
+            scale,
+            world_matrix: Matrix4x4f::identity(),
+            dirty: true,
+        }
+    }
+
+    pub fn position(&self) -> Vector3f {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: Vector3f) {
+        self.position = position;
+        self.dirty = true;
+    }
+
+    pub fn translate(&mut self, offset: &Vector3f) {
+        self.set_position(self.position + offset);
+    }
+
+    pub fn rotation(&self) -> Vector3f {
+        self.rotation
+    }
+
+    pub fn set_rotation(&mut self, rotation: Vector3f) {
+        self.rotation = rotation;
+        self.dirty = true;
+    }
+
+    pub fn scale(&self) -> Vector3f {
+        self.scale
+    }
+
+    pub fn set_scale(&mut self, scale: Vector3f) {
+        self.scale = scale;
+        self.dirty = true;
+    }
+
+    pub fn local_matrix(&self) -> Matrix4x4f {
+        Matrix4x4f::translation(&self.position)
+            * Matrix4x4f::rotation(&self.rotation)
+            * Matrix4x4f::scale(&self.scale)
+    }
+
+    /// As of the last transform update, so it lags behind local changes made
+    /// since.
+    pub fn world_matrix(&self) -> &Matrix4x4f {
+        &self.world_matrix
+    }
+
+    pub fn world_position(&self) -> Vector3f {
+        self.world_matrix.transform_point(&Vector3f::default())
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    pub(crate) fn update_world_matrix(&mut self, parent_world_matrix: Option<&Matrix4x4f>) {
+        self.world_matrix = match parent_world_matrix {
+            Some(parent_world_matrix) => parent_world_matrix * self.local_matrix(),
+            None => self.local_matrix(),
+        };
+        self.dirty = false;
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::new(
+            Vector3f::default(),
+            Vector3f::default(),
+            Vector3f::new(1.0, 1.0, 1.0),
+        )
+    }
+}
@@ -1,7 +1,6 @@
 use std::io::BufWriter;
 use std::io::{self};
 use std::num::NonZeroU32;
-use std::time::Instant;
 
 use engine::renderer::opengl_renderer::OpenGlRenderer;
 use engine::renderer::Renderer;
@@ -38,16 +37,16 @@ fn main() {
     let scene = Scene::new();
 
     let game_object = scene.add_game_object();
-    game_object.add_logic_component(move |engine_context, _| {
+    let game_object = scene.game_object(game_object).unwrap();
+    game_object.add_logic_component(move |engine_context, _, _| {
         engine_context
             .logger_client()
             .log(Debug, "Hello from logic component!");
     });
 
-    let engine = Engine::new(scheduler, logger_client, scene.clone());
+    let mut engine = Engine::new(scheduler, logger_client, scene);
 
     let event_loop = EventLoop::new();
-    let mut previous_update = Instant::now();
     let inner_size = PhysicalSize::new(1024, 768);
     let window = WindowBuilder::new()
         .with_inner_size(inner_size)
@@ -77,12 +76,8 @@ fn main() {
 
         match event {
             Event::MainEventsCleared => {
-                let now = Instant::now();
-                let delta_time = now - previous_update;
-                previous_update = now;
-
-                engine.update(delta_time);
-                renderer.render(&scene);
+                engine.tick();
+                renderer.render(&engine.engine_context().scenes(), engine.render_passes());
             }
             Event::WindowEvent {
                 event:
@@ -0,0 +1,150 @@
+use std::error::Error;
+use std::fmt::Debug;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::ops::Index;
+use std::ops::IndexMut;
+
+/// Implemented by [`smart_enum!`](crate::smart_enum) for every enum it
+/// generates.
+pub trait SmartEnum: Copy + 'static {
+    const COUNT: usize;
+
+    /// `[V; Self::COUNT]`, spelled out because array lengths can't depend on
+    /// generic parameters.
+    type Array<V>: AsRef<[V]> + AsMut<[V]>;
+
+    fn index(self) -> usize;
+    fn from_index(index: usize) -> Option<Self>;
+    fn array_from_fn<V, F: FnMut(usize) -> V>(fun: F) -> Self::Array<V>;
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseEnumError {
+    type_name: &'static str,
+    value: String,
+}
+
+impl ParseEnumError {
+    pub fn new(type_name: &'static str, value: &str) -> Self {
+        Self {
+            type_name,
+            value: value.to_owned(),
+        }
+    }
+}
+
+impl Display for ParseEnumError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "unknown {} `{}`", self.type_name, self.value)
+    }
+}
+
+impl Error for ParseEnumError {}
+
+/// A map with one value per variant of `E`, stored inline in an array.
+pub struct EnumMap<E: SmartEnum, V> {
+    values: E::Array<V>,
+}
+
+impl<E: SmartEnum, V> EnumMap<E, V> {
+    pub fn new<F: FnMut(E) -> V>(mut fun: F) -> Self {
+        Self {
+            values: E::array_from_fn(|index| fun(E::from_index(index).unwrap())),
+        }
+    }
+
+    pub fn get(&self, key: E) -> &V {
+        &self.values.as_ref()[key.index()]
+    }
+
+    pub fn get_mut(&mut self, key: E) -> &mut V {
+        &mut self.values.as_mut()[key.index()]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (E, &V)> {
+        self.values
+            .as_ref()
+            .iter()
+            .enumerate()
+            .map(|(index, value)| (E::from_index(index).unwrap(), value))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (E, &mut V)> {
+        self.values
+            .as_mut()
+            .iter_mut()
+            .enumerate()
+            .map(|(index, value)| (E::from_index(index).unwrap(), value))
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.values.as_ref().iter()
+    }
+}
+
+impl<E: SmartEnum, V: Default> Default for EnumMap<E, V> {
+    fn default() -> Self {
+        Self::new(|_| V::default())
+    }
+}
+
+impl<E: SmartEnum, V: Clone> Clone for EnumMap<E, V> {
+    fn clone(&self) -> Self {
+        Self::new(|key| self.get(key).clone())
+    }
+}
+
+impl<E: SmartEnum + Debug, V: Debug> Debug for EnumMap<E, V> {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<E: SmartEnum, V> Index<E> for EnumMap<E, V> {
+    type Output = V;
+
+    fn index(&self, key: E) -> &Self::Output {
+        self.get(key)
+    }
+}
+
+impl<E: SmartEnum, V> IndexMut<E> for EnumMap<E, V> {
+    fn index_mut(&mut self, key: E) -> &mut Self::Output {
+        self.get_mut(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EnumMap;
+    use super::SmartEnum;
+    use crate::smart_enum;
+
+    smart_enum!(pub, Direction, North, East, South, West);
+
+    #[test]
+    fn test_smart_enum() {
+        assert_eq!(4, Direction::COUNT);
+        assert_eq!(2, Direction::South.index());
+        assert_eq!(Some(Direction::West), Direction::from_index(3));
+        assert_eq!(Ok(Direction::East), "East".parse());
+        assert_eq!(
+            "unknown Direction `Up`",
+            "Up".parse::<Direction>().unwrap_err().to_string()
+        );
+        assert!(Direction::North < Direction::West);
+    }
+
+    #[test]
+    fn test_enum_map() {
+        let mut map = EnumMap::new(|direction: Direction| direction.index() * 10);
+        map[Direction::East] += 1;
+
+        assert_eq!(11, map[Direction::East]);
+        assert_eq!(
+            vec![0, 11, 20, 30],
+            map.values().copied().collect::<Vec<_>>()
+        );
+    }
+}
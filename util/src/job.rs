@@ -118,11 +118,8 @@ macro_rules! thread_pool_descriptor {
 macro_rules! thread_pool {
     ($i:ident, $($j:ident:$k:literal),*) => {
         mod thread_pool {
-            use $crate::thread_category;
-            use $crate::thread_pool_descriptor;
-
-            thread_category!($i, $($j),*);
-            thread_pool_descriptor!($i, $($j:$k),*);
+            $crate::thread_category!($i, $($j),*);
+            $crate::thread_pool_descriptor!($i, $($j:$k),*);
         }
 
         pub use thread_pool::$i;
@@ -1,12 +1,17 @@
+pub mod enum_map;
 pub mod job;
 pub mod logger;
 pub mod math;
+pub mod noise;
+pub mod random;
 pub mod runtime_id;
+pub mod slot_map;
+pub mod snapshot;
 
 #[macro_export]
 macro_rules! smart_enum {
     ($v:vis, $i:ident, $($j:ident),*) => {
-        #[derive(Copy, Clone, Eq, Hash, PartialEq)]
+        #[derive(Copy, Clone, Eq, Hash, PartialEq, PartialOrd, Ord, Debug)]
         $v enum $i {
             $($j),*
         }
@@ -17,6 +22,24 @@ macro_rules! smart_enum {
             }
         }
 
+        impl $crate::enum_map::SmartEnum for $i {
+            const COUNT: usize = [$($i::$j),*].len();
+
+            type Array<V> = [V; <$i as $crate::enum_map::SmartEnum>::COUNT];
+
+            fn index(self) -> usize {
+                self as usize
+            }
+
+            fn from_index(index: usize) -> Option<Self> {
+                [$($i::$j),*].get(index).copied()
+            }
+
+            fn array_from_fn<V, F: FnMut(usize) -> V>(fun: F) -> Self::Array<V> {
+                std::array::from_fn(fun)
+            }
+        }
+
         impl std::fmt::Display for $i {
             fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
                 write!(f, "{}", <&str>::from(*self))
@@ -30,11 +53,87 @@ macro_rules! smart_enum {
                 }
             }
         }
+
+        impl std::str::FromStr for $i {
+            type Err = $crate::enum_map::ParseEnumError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    $(stringify!($j) => Ok($i::$j),)*
+                    _ => Err($crate::enum_map::ParseEnumError::new(stringify!($i), s)),
+                }
+            }
+        }
     };
 }
 
+/// Declares a struct whose mutable state lives in `$impl_struct_name` behind
+/// interior mutability. The default form uses a `Mutex` and panics if it is
+/// poisoned. A leading `Mutex`, `RwLock` or `Snapshot` selects the wrapper,
+/// optionally followed by `recover_poison` to keep using the state after a
+/// panic in another thread:
+///
+/// - `Mutex`: `lock_inner()`.
+/// - `RwLock`: `read_inner()` and `write_inner()`, readers share the lock.
+/// - `Snapshot`: `load_inner()` and `update_inner()`, see
+///   [`Snapshot`](crate::snapshot::Snapshot). Never poisoned.
 #[macro_export]
 macro_rules! internal_mut_struct {
+    (Mutex $(, $recover_poison:ident)?; $struct_name:ident, $impl_struct_name:ty $(, $field_name:ident: $field_type: ty)*) => {
+        pub struct $struct_name {
+            inner: std::sync::Mutex<$impl_struct_name>,
+            $($field_name: $field_type),*
+        }
+
+        impl $struct_name {
+            #[allow(dead_code)]
+            fn lock_inner(&self) -> std::sync::MutexGuard<'_, $impl_struct_name> {
+                $crate::internal_mut_struct!(@unwrap self.inner.lock() $(, $recover_poison)?)
+            }
+        }
+    };
+    (RwLock $(, $recover_poison:ident)?; $struct_name:ident, $impl_struct_name:ty $(, $field_name:ident: $field_type: ty)*) => {
+        pub struct $struct_name {
+            inner: std::sync::RwLock<$impl_struct_name>,
+            $($field_name: $field_type),*
+        }
+
+        impl $struct_name {
+            #[allow(dead_code)]
+            fn read_inner(&self) -> std::sync::RwLockReadGuard<'_, $impl_struct_name> {
+                $crate::internal_mut_struct!(@unwrap self.inner.read() $(, $recover_poison)?)
+            }
+
+            #[allow(dead_code)]
+            fn write_inner(&self) -> std::sync::RwLockWriteGuard<'_, $impl_struct_name> {
+                $crate::internal_mut_struct!(@unwrap self.inner.write() $(, $recover_poison)?)
+            }
+        }
+    };
+    (Snapshot; $struct_name:ident, $impl_struct_name:ty $(, $field_name:ident: $field_type: ty)*) => {
+        pub struct $struct_name {
+            inner: $crate::snapshot::Snapshot<$impl_struct_name>,
+            $($field_name: $field_type),*
+        }
+
+        impl $struct_name {
+            #[allow(dead_code)]
+            fn load_inner(&self) -> std::sync::Arc<$impl_struct_name> {
+                self.inner.load()
+            }
+
+            #[allow(dead_code)]
+            fn update_inner<R, F: FnOnce(&mut $impl_struct_name) -> R>(&self, fun: F) -> R {
+                self.inner.update(fun)
+            }
+        }
+    };
+    (@unwrap $result:expr) => {
+        $result.unwrap()
+    };
+    (@unwrap $result:expr, recover_poison) => {
+        $result.unwrap_or_else(std::sync::PoisonError::into_inner)
+    };
     ($struct_name:ident, $impl_struct_name:ty $(, $field_name:ident: $field_type: ty)*) => {
         pub struct $struct_name {
             inner: Mutex<$impl_struct_name>,
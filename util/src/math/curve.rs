@@ -0,0 +1,293 @@
+use std::ops::Add;
+use std::ops::Mul;
+use std::ops::Sub;
+
+use super::vector::Vector2f;
+use super::vector::Vector3f;
+
+pub trait CurvePoint:
+    Add<Output = Self> + Sub<Output = Self> + Mul<f64, Output = Self> + Copy
+{
+    fn length(&self) -> f64;
+}
+
+impl CurvePoint for Vector2f {
+    fn length(&self) -> f64 {
+        Vector2f::length(self)
+    }
+}
+
+impl CurvePoint for Vector3f {
+    fn length(&self) -> f64 {
+        Vector3f::length(self)
+    }
+}
+
+pub trait Curve<P: CurvePoint> {
+    /// Evaluates the curve at `t` in `[0, 1]`.
+    fn point(&self, t: f64) -> P;
+
+    /// The first derivative with respect to `t`.
+    fn tangent(&self, t: f64) -> P;
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct QuadraticBezier<P> {
+    pub p0: P,
+    pub p1: P,
+    pub p2: P,
+}
+
+impl<P> QuadraticBezier<P> {
+    pub fn new(p0: P, p1: P, p2: P) -> Self {
+        Self { p0, p1, p2 }
+    }
+}
+
+impl<P: CurvePoint> Curve<P> for QuadraticBezier<P> {
+    fn point(&self, t: f64) -> P {
+        let u = 1.0 - t;
+        self.p0 * (u * u) + self.p1 * (2.0 * u * t) + self.p2 * (t * t)
+    }
+
+    fn tangent(&self, t: f64) -> P {
+        (self.p1 - self.p0) * (2.0 * (1.0 - t)) + (self.p2 - self.p1) * (2.0 * t)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CubicBezier<P> {
+    pub p0: P,
+    pub p1: P,
+    pub p2: P,
+    pub p3: P,
+}
+
+impl<P> CubicBezier<P> {
+    pub fn new(p0: P, p1: P, p2: P, p3: P) -> Self {
+        Self { p0, p1, p2, p3 }
+    }
+}
+
+impl<P: CurvePoint> Curve<P> for CubicBezier<P> {
+    fn point(&self, t: f64) -> P {
+        let u = 1.0 - t;
+        self.p0 * (u * u * u)
+            + self.p1 * (3.0 * u * u * t)
+            + self.p2 * (3.0 * u * t * t)
+            + self.p3 * (t * t * t)
+    }
+
+    fn tangent(&self, t: f64) -> P {
+        let u = 1.0 - t;
+        (self.p1 - self.p0) * (3.0 * u * u)
+            + (self.p2 - self.p1) * (6.0 * u * t)
+            + (self.p3 - self.p2) * (3.0 * t * t)
+    }
+}
+
+/// A cubic segment from `p0` to `p1` with the tangents `m0` and `m1`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Hermite<P> {
+    pub p0: P,
+    pub m0: P,
+    pub p1: P,
+    pub m1: P,
+}
+
+impl<P> Hermite<P> {
+    pub fn new(p0: P, m0: P, p1: P, m1: P) -> Self {
+        Self { p0, m0, p1, m1 }
+    }
+}
+
+impl<P: CurvePoint> Curve<P> for Hermite<P> {
+    fn point(&self, t: f64) -> P {
+        let t2 = t * t;
+        let t3 = t2 * t;
+        self.p0 * (2.0 * t3 - 3.0 * t2 + 1.0)
+            + self.m0 * (t3 - 2.0 * t2 + t)
+            + self.p1 * (-2.0 * t3 + 3.0 * t2)
+            + self.m1 * (t3 - t2)
+    }
+
+    fn tangent(&self, t: f64) -> P {
+        let t2 = t * t;
+        self.p0 * (6.0 * t2 - 6.0 * t)
+            + self.m0 * (3.0 * t2 - 4.0 * t + 1.0)
+            + self.p1 * (-6.0 * t2 + 6.0 * t)
+            + self.m1 * (3.0 * t2 - 2.0 * t)
+    }
+}
+
+/// A uniform Catmull-Rom spline passing through every control point. The
+/// whole spline is mapped onto `t` in `[0, 1]`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CatmullRom<P> {
+    points: Vec<P>,
+}
+
+impl<P: CurvePoint> CatmullRom<P> {
+    pub fn new(points: Vec<P>) -> Self {
+        assert!(points.len() >= 2, "a spline needs at least two points");
+        Self { points }
+    }
+
+    pub fn points(&self) -> &[P] {
+        &self.points
+    }
+
+    fn segment(&self, t: f64) -> (Hermite<P>, f64) {
+        let segment_count = self.points.len() - 1;
+        let t = t.clamp(0.0, 1.0) * segment_count as f64;
+        let index = (t as usize).min(segment_count - 1);
+
+        let p1 = self.points[index];
+        let p2 = self.points[index + 1];
+        let p0 = if index > 0 {
+            self.points[index - 1]
+        } else {
+            p1 + (p1 - p2)
+        };
+        let p3 = if index + 2 < self.points.len() {
+            self.points[index + 2]
+        } else {
+            p2 + (p2 - p1)
+        };
+
+        (
+            Hermite::new(p1, (p2 - p0) * 0.5, p2, (p3 - p1) * 0.5),
+            t - index as f64,
+        )
+    }
+}
+
+impl<P: CurvePoint> Curve<P> for CatmullRom<P> {
+    fn point(&self, t: f64) -> P {
+        let (segment, t) = self.segment(t);
+        segment.point(t)
+    }
+
+    fn tangent(&self, t: f64) -> P {
+        let (segment, t) = self.segment(t);
+        segment.tangent(t) * (self.points.len() - 1) as f64
+    }
+}
+
+/// Reparameterizes a curve by distance travelled using a sampled lookup
+/// table, so moving along it at a constant speed is straightforward.
+pub struct ArcLength<C> {
+    curve: C,
+    distances: Vec<f64>,
+}
+
+impl<C> ArcLength<C> {
+    pub fn new<P: CurvePoint>(curve: C, samples: usize) -> Self
+    where
+        C: Curve<P>,
+    {
+        let samples = samples.max(1);
+        let mut distances = Vec::with_capacity(samples + 1);
+        distances.push(0.0);
+
+        let mut previous = curve.point(0.0);
+        for i in 1..=samples {
+            let point = curve.point(i as f64 / samples as f64);
+            distances.push(distances[i - 1] + (point - previous).length());
+            previous = point;
+        }
+
+        Self { curve, distances }
+    }
+
+    pub fn curve(&self) -> &C {
+        &self.curve
+    }
+
+    pub fn length(&self) -> f64 {
+        *self.distances.last().unwrap()
+    }
+
+    /// Maps a distance along the curve to the curve parameter `t`.
+    pub fn parameter(&self, distance: f64) -> f64 {
+        let distance = distance.clamp(0.0, self.length());
+        let index = self
+            .distances
+            .partition_point(|d| *d < distance)
+            .clamp(1, self.distances.len() - 1);
+
+        let start = self.distances[index - 1];
+        let span = self.distances[index] - start;
+        let fraction = if span > 0.0 {
+            (distance - start) / span
+        } else {
+            0.0
+        };
+
+        (index as f64 - 1.0 + fraction) / (self.distances.len() - 1) as f64
+    }
+
+    pub fn point_at_distance<P: CurvePoint>(&self, distance: f64) -> P
+    where
+        C: Curve<P>,
+    {
+        self.curve.point(self.parameter(distance))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ArcLength;
+    use super::CatmullRom;
+    use super::CubicBezier;
+    use super::Curve;
+    use super::QuadraticBezier;
+    use crate::math::vector::Vector2f;
+
+    #[test]
+    fn test_bezier() {
+        let quadratic = QuadraticBezier::new(
+            Vector2f::new(0.0, 0.0),
+            Vector2f::new(1.0, 2.0),
+            Vector2f::new(2.0, 0.0),
+        );
+        assert_eq!(Vector2f::new(1.0, 1.0), quadratic.point(0.5));
+
+        let cubic = CubicBezier::new(
+            Vector2f::new(0.0, 0.0),
+            Vector2f::new(0.0, 1.0),
+            Vector2f::new(1.0, 1.0),
+            Vector2f::new(1.0, 0.0),
+        );
+        assert_eq!(Vector2f::new(0.0, 0.0), cubic.point(0.0));
+        assert_eq!(Vector2f::new(0.5, 0.75), cubic.point(0.5));
+        assert_eq!(Vector2f::new(1.0, 0.0), cubic.point(1.0));
+    }
+
+    #[test]
+    fn test_catmull_rom_passes_through_points() {
+        let points = vec![
+            Vector2f::new(0.0, 0.0),
+            Vector2f::new(1.0, 1.0),
+            Vector2f::new(2.0, 0.0),
+        ];
+        let spline = CatmullRom::new(points.clone());
+        assert_eq!(points[0], spline.point(0.0));
+        assert_eq!(points[1], spline.point(0.5));
+        assert_eq!(points[2], spline.point(1.0));
+    }
+
+    #[test]
+    fn test_arc_length() {
+        let line = QuadraticBezier::new(
+            Vector2f::new(0.0, 0.0),
+            Vector2f::new(1.0, 0.0),
+            Vector2f::new(4.0, 0.0),
+        );
+        let arc_length = ArcLength::new(line, 256);
+        assert!((arc_length.length() - 4.0).abs() < 1e-9);
+
+        let point: Vector2f = arc_length.point_at_distance(2.0);
+        assert!((point.x - 2.0).abs() < 1e-3);
+    }
+}
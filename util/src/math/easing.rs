@@ -0,0 +1,189 @@
+use std::f64::consts::PI;
+
+use crate::smart_enum;
+
+const BACK_OVERSHOOT: f64 = 1.70158;
+const BOUNCE_STRENGTH: f64 = 7.5625;
+const BOUNCE_DURATION: f64 = 2.75;
+
+smart_enum!(
+    pub,
+    Easing,
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    ElasticIn,
+    ElasticOut,
+    ElasticInOut,
+    BounceIn,
+    BounceOut,
+    BounceInOut,
+    BackIn,
+    BackOut,
+    BackInOut
+);
+
+impl Easing {
+    /// Maps `t` in `[0, 1]` onto the eased progress, which is 0 at `t == 0`
+    /// and 1 at `t == 1`. Elastic and back easings overshoot in between.
+    pub fn apply(self, t: f64) -> f64 {
+        let fun = match self {
+            Easing::Linear => linear,
+            Easing::QuadIn => quad_in,
+            Easing::QuadOut => quad_out,
+            Easing::QuadInOut => quad_in_out,
+            Easing::CubicIn => cubic_in,
+            Easing::CubicOut => cubic_out,
+            Easing::CubicInOut => cubic_in_out,
+            Easing::ElasticIn => elastic_in,
+            Easing::ElasticOut => elastic_out,
+            Easing::ElasticInOut => elastic_in_out,
+            Easing::BounceIn => bounce_in,
+            Easing::BounceOut => bounce_out,
+            Easing::BounceInOut => bounce_in_out,
+            Easing::BackIn => back_in,
+            Easing::BackOut => back_out,
+            Easing::BackInOut => back_in_out,
+        };
+
+        fun(t.clamp(0.0, 1.0))
+    }
+}
+
+pub fn linear(t: f64) -> f64 {
+    t
+}
+
+pub fn quad_in(t: f64) -> f64 {
+    t * t
+}
+
+pub fn quad_out(t: f64) -> f64 {
+    1.0 - (1.0 - t).powi(2)
+}
+
+pub fn quad_in_out(t: f64) -> f64 {
+    if t < 0.5 {
+        2.0 * t * t
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+    }
+}
+
+pub fn cubic_in(t: f64) -> f64 {
+    t * t * t
+}
+
+pub fn cubic_out(t: f64) -> f64 {
+    1.0 - (1.0 - t).powi(3)
+}
+
+pub fn cubic_in_out(t: f64) -> f64 {
+    if t < 0.5 {
+        4.0 * t * t * t
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+    }
+}
+
+pub fn elastic_in(t: f64) -> f64 {
+    const PERIOD: f64 = 2.0 * PI / 3.0;
+
+    if t <= 0.0 || t >= 1.0 {
+        t.clamp(0.0, 1.0)
+    } else {
+        -(2.0f64.powf(10.0 * t - 10.0)) * ((t * 10.0 - 10.75) * PERIOD).sin()
+    }
+}
+
+pub fn elastic_out(t: f64) -> f64 {
+    const PERIOD: f64 = 2.0 * PI / 3.0;
+
+    if t <= 0.0 || t >= 1.0 {
+        t.clamp(0.0, 1.0)
+    } else {
+        2.0f64.powf(-10.0 * t) * ((t * 10.0 - 0.75) * PERIOD).sin() + 1.0
+    }
+}
+
+pub fn elastic_in_out(t: f64) -> f64 {
+    const PERIOD: f64 = 2.0 * PI / 4.5;
+
+    if t <= 0.0 || t >= 1.0 {
+        t.clamp(0.0, 1.0)
+    } else if t < 0.5 {
+        -(2.0f64.powf(20.0 * t - 10.0) * ((20.0 * t - 11.125) * PERIOD).sin()) / 2.0
+    } else {
+        2.0f64.powf(-20.0 * t + 10.0) * ((20.0 * t - 11.125) * PERIOD).sin() / 2.0 + 1.0
+    }
+}
+
+pub fn bounce_out(t: f64) -> f64 {
+    if t < 1.0 / BOUNCE_DURATION {
+        BOUNCE_STRENGTH * t * t
+    } else if t < 2.0 / BOUNCE_DURATION {
+        let t = t - 1.5 / BOUNCE_DURATION;
+        BOUNCE_STRENGTH * t * t + 0.75
+    } else if t < 2.5 / BOUNCE_DURATION {
+        let t = t - 2.25 / BOUNCE_DURATION;
+        BOUNCE_STRENGTH * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / BOUNCE_DURATION;
+        BOUNCE_STRENGTH * t * t + 0.984375
+    }
+}
+
+pub fn bounce_in(t: f64) -> f64 {
+    1.0 - bounce_out(1.0 - t)
+}
+
+pub fn bounce_in_out(t: f64) -> f64 {
+    if t < 0.5 {
+        (1.0 - bounce_out(1.0 - 2.0 * t)) / 2.0
+    } else {
+        (1.0 + bounce_out(2.0 * t - 1.0)) / 2.0
+    }
+}
+
+pub fn back_in(t: f64) -> f64 {
+    (BACK_OVERSHOOT + 1.0) * t * t * t - BACK_OVERSHOOT * t * t
+}
+
+pub fn back_out(t: f64) -> f64 {
+    1.0 + (BACK_OVERSHOOT + 1.0) * (t - 1.0).powi(3) + BACK_OVERSHOOT * (t - 1.0).powi(2)
+}
+
+pub fn back_in_out(t: f64) -> f64 {
+    const OVERSHOOT: f64 = BACK_OVERSHOOT * 1.525;
+
+    if t < 0.5 {
+        (2.0 * t).powi(2) * ((OVERSHOOT + 1.0) * 2.0 * t - OVERSHOOT) / 2.0
+    } else {
+        ((2.0 * t - 2.0).powi(2) * ((OVERSHOOT + 1.0) * (t * 2.0 - 2.0) + OVERSHOOT) + 2.0) / 2.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Easing;
+
+    #[test]
+    fn test_easing_endpoints() {
+        for easing in Easing::values() {
+            assert!(easing.apply(0.0).abs() < 1e-9, "{easing} at 0");
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-9, "{easing} at 1");
+        }
+    }
+
+    #[test]
+    fn test_easing_midpoint() {
+        assert_eq!(0.25, Easing::QuadIn.apply(0.5));
+        assert_eq!(0.75, Easing::QuadOut.apply(0.5));
+        assert_eq!(0.5, Easing::CubicInOut.apply(0.5));
+        assert!(Easing::BackIn.apply(0.2) < 0.0);
+    }
+}
@@ -0,0 +1,446 @@
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::ops::Add;
+use std::ops::AddAssign;
+use std::ops::Div;
+use std::ops::DivAssign;
+use std::ops::Mul;
+use std::ops::MulAssign;
+use std::ops::Neg;
+use std::ops::Sub;
+use std::ops::SubAssign;
+
+use super::vector::VectorType;
+
+/// A fixed-point number stored in the integer `I` with `FRAC` fractional
+/// bits. Every operation is integer arithmetic, so results are bit-identical
+/// on every machine. Unlike primitive integers, the arithmetic operators
+/// panic on overflow in release builds too; use the `checked_`,
+/// `saturating_` or `wrapping_` methods to handle it explicitly.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Fixed<I, const FRAC: u32> {
+    bits: I,
+}
+
+pub type I16F16 = Fixed<i32, 16>;
+pub type I32F32 = Fixed<i64, 32>;
+
+/// Pi scaled by 2^61, the most precision that fits in an `i64`.
+const PI_BITS_61: i128 = 0x6487_ED51_10B4_611A;
+
+macro_rules! define_fixed {
+    ($base:ty, $wide:ty) => {
+        impl<const FRAC: u32> Fixed<$base, FRAC> {
+            const FRAC_IS_VALID: () = assert!(
+                FRAC >= 1 && FRAC <= <$base>::BITS - 4,
+                "FRAC must leave room for the integer part of two pi"
+            );
+
+            pub const ZERO: Self = Self { bits: 0 };
+            pub const MIN: Self = Self { bits: <$base>::MIN };
+            pub const MAX: Self = Self { bits: <$base>::MAX };
+
+            pub const fn from_bits(bits: $base) -> Self {
+                let () = Self::FRAC_IS_VALID;
+                Self { bits }
+            }
+
+            pub const fn to_bits(self) -> $base {
+                self.bits
+            }
+
+            pub fn one() -> Self {
+                Self::from_bits(1 << FRAC)
+            }
+
+            pub fn pi() -> Self {
+                Self::from_bits((PI_BITS_61 >> (61 - FRAC)) as $base)
+            }
+
+            pub fn checked_from_int(value: $base) -> Option<Self> {
+                value.checked_mul(1 << FRAC).map(Self::from_bits)
+            }
+
+            pub fn from_int(value: $base) -> Self {
+                Self::checked_from_int(value).expect("integer out of fixed-point range")
+            }
+
+            /// Rounds towards negative infinity.
+            pub fn to_int(self) -> $base {
+                self.bits >> FRAC
+            }
+
+            /// Meant for constants and tooling: converting a given `f64` is
+            /// deterministic, but floats computed at runtime may not be.
+            pub fn from_f64(value: f64) -> Self {
+                Self::from_bits((value * (1u64 << FRAC) as f64).round() as $base)
+            }
+
+            pub fn to_f64(self) -> f64 {
+                self.bits as f64 / (1u64 << FRAC) as f64
+            }
+
+            pub fn checked_add(self, rhs: Self) -> Option<Self> {
+                self.bits.checked_add(rhs.bits).map(Self::from_bits)
+            }
+
+            pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+                self.bits.checked_sub(rhs.bits).map(Self::from_bits)
+            }
+
+            pub fn checked_neg(self) -> Option<Self> {
+                self.bits.checked_neg().map(Self::from_bits)
+            }
+
+            /// Rounds to the nearest representable value.
+            pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+                <$base>::try_from(self.wide_mul(rhs))
+                    .ok()
+                    .map(Self::from_bits)
+            }
+
+            /// Rounds towards zero. Returns `None` when dividing by zero.
+            pub fn checked_div(self, rhs: Self) -> Option<Self> {
+                if rhs.bits == 0 {
+                    return None;
+                }
+
+                <$base>::try_from(((self.bits as $wide) << FRAC) / rhs.bits as $wide)
+                    .ok()
+                    .map(Self::from_bits)
+            }
+
+            pub fn saturating_add(self, rhs: Self) -> Self {
+                Self::from_bits(self.bits.saturating_add(rhs.bits))
+            }
+
+            pub fn saturating_sub(self, rhs: Self) -> Self {
+                Self::from_bits(self.bits.saturating_sub(rhs.bits))
+            }
+
+            pub fn saturating_mul(self, rhs: Self) -> Self {
+                self.checked_mul(rhs)
+                    .unwrap_or(if (self.bits < 0) != (rhs.bits < 0) {
+                        Self::MIN
+                    } else {
+                        Self::MAX
+                    })
+            }
+
+            /// Panics when dividing by zero.
+            pub fn saturating_div(self, rhs: Self) -> Self {
+                assert!(rhs.bits != 0, "attempt to divide by zero");
+                self.checked_div(rhs)
+                    .unwrap_or(if (self.bits < 0) != (rhs.bits < 0) {
+                        Self::MIN
+                    } else {
+                        Self::MAX
+                    })
+            }
+
+            pub fn wrapping_add(self, rhs: Self) -> Self {
+                Self::from_bits(self.bits.wrapping_add(rhs.bits))
+            }
+
+            pub fn wrapping_sub(self, rhs: Self) -> Self {
+                Self::from_bits(self.bits.wrapping_sub(rhs.bits))
+            }
+
+            pub fn wrapping_mul(self, rhs: Self) -> Self {
+                Self::from_bits(self.wide_mul(rhs) as $base)
+            }
+
+            pub fn wrapping_neg(self) -> Self {
+                Self::from_bits(self.bits.wrapping_neg())
+            }
+
+            fn wide_mul(self, rhs: Self) -> $wide {
+                (self.bits as $wide * rhs.bits as $wide + (1 << (FRAC - 1))) >> FRAC
+            }
+
+            pub fn abs(self) -> Self {
+                if self.bits < 0 {
+                    -self
+                } else {
+                    self
+                }
+            }
+
+            pub fn floor(self) -> Self {
+                Self::from_bits(self.bits & !((1 << FRAC) - 1))
+            }
+
+            /// Like [`f64::fract`], negative for negative numbers.
+            pub fn fract(self) -> Self {
+                let fract = self.bits & ((1 << FRAC) - 1);
+                if self.bits < 0 && fract != 0 {
+                    Self::from_bits(fract - (1 << FRAC))
+                } else {
+                    Self::from_bits(fract)
+                }
+            }
+
+            /// Panics on negative input.
+            pub fn sqrt(self) -> Self {
+                assert!(self.bits >= 0, "square root of a negative number");
+                Self::from_bits(((self.bits as u128) << FRAC).isqrt() as $base)
+            }
+
+            /// Maps an angle in radians into `[-pi, pi]`.
+            fn wrap_angle(self) -> Self {
+                let pi = Self::pi();
+                let tau = pi + pi;
+                let angle = Self::from_bits(self.bits % tau.bits);
+                if angle > pi {
+                    angle - tau
+                } else if angle < -pi {
+                    angle + tau
+                } else {
+                    angle
+                }
+            }
+
+            /// Polynomial approximation with an error below `4e-6` plus
+            /// rounding error.
+            pub fn sin(self) -> Self {
+                let pi = Self::pi();
+                let half_pi = Self::from_bits(pi.bits / 2);
+
+                let mut x = self.wrap_angle();
+                if x > half_pi {
+                    x = pi - x;
+                } else if x < -half_pi {
+                    x = -pi - x;
+                }
+
+                let x2 = x * x;
+                let c3 = Self::from_f64(-1.0 / 6.0);
+                let c5 = Self::from_f64(1.0 / 120.0);
+                let c7 = Self::from_f64(-1.0 / 5040.0);
+                let c9 = Self::from_f64(1.0 / 362880.0);
+
+                x * (Self::one() + x2 * (c3 + x2 * (c5 + x2 * (c7 + x2 * c9))))
+            }
+
+            pub fn cos(self) -> Self {
+                let half_pi = Self::from_bits(Self::pi().bits / 2);
+                (half_pi - self.wrap_angle()).sin()
+            }
+
+            /// Saturates where the cosine rounds to zero, e.g. at `pi / 2`.
+            pub fn tan(self) -> Self {
+                let (sin, cos) = (self.sin(), self.cos());
+                if cos.bits == 0 {
+                    return if sin.bits < 0 { Self::MIN } else { Self::MAX };
+                }
+
+                sin.saturating_div(cos)
+            }
+
+            /// Only valid for `|self| <= 1`.
+            fn atan_unit(self) -> Self {
+                let x2 = self * self;
+                let c1 = Self::from_f64(0.9998660);
+                let c3 = Self::from_f64(-0.3302995);
+                let c5 = Self::from_f64(0.1801410);
+                let c7 = Self::from_f64(-0.0851330);
+                let c9 = Self::from_f64(0.0208351);
+
+                self * (c1 + x2 * (c3 + x2 * (c5 + x2 * (c7 + x2 * c9))))
+            }
+
+            /// Polynomial approximation with an error below `1e-5` plus
+            /// rounding error.
+            pub fn atan(self) -> Self {
+                let half_pi = Self::from_bits(Self::pi().bits / 2);
+                if self.abs() <= Self::one() {
+                    self.atan_unit()
+                } else if self.bits > 0 {
+                    half_pi - (Self::one() / self).atan_unit()
+                } else {
+                    -half_pi - (Self::one() / self).atan_unit()
+                }
+            }
+
+            pub fn atan2(self, x: Self) -> Self {
+                let y = self;
+                let pi = Self::pi();
+                let half_pi = Self::from_bits(pi.bits / 2);
+
+                if x.bits == 0 && y.bits == 0 {
+                    Self::ZERO
+                } else if y.abs() <= x.abs() {
+                    let angle = (y / x).atan_unit();
+                    if x.bits > 0 {
+                        angle
+                    } else if y.bits >= 0 {
+                        angle + pi
+                    } else {
+                        angle - pi
+                    }
+                } else if y.bits > 0 {
+                    half_pi - (x / y).atan_unit()
+                } else {
+                    -half_pi - (x / y).atan_unit()
+                }
+            }
+        }
+
+        impl<const FRAC: u32> Add for Fixed<$base, FRAC> {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self::Output {
+                self.checked_add(rhs).expect("attempt to add with overflow")
+            }
+        }
+
+        impl<const FRAC: u32> Sub for Fixed<$base, FRAC> {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self::Output {
+                self.checked_sub(rhs)
+                    .expect("attempt to subtract with overflow")
+            }
+        }
+
+        impl<const FRAC: u32> Mul for Fixed<$base, FRAC> {
+            type Output = Self;
+
+            fn mul(self, rhs: Self) -> Self::Output {
+                self.checked_mul(rhs)
+                    .expect("attempt to multiply with overflow")
+            }
+        }
+
+        impl<const FRAC: u32> Div for Fixed<$base, FRAC> {
+            type Output = Self;
+
+            fn div(self, rhs: Self) -> Self::Output {
+                assert!(rhs.bits != 0, "attempt to divide by zero");
+                self.checked_div(rhs)
+                    .expect("attempt to divide with overflow")
+            }
+        }
+
+        impl<const FRAC: u32> Neg for Fixed<$base, FRAC> {
+            type Output = Self;
+
+            fn neg(self) -> Self::Output {
+                self.checked_neg().expect("attempt to negate with overflow")
+            }
+        }
+
+        impl<const FRAC: u32> AddAssign for Fixed<$base, FRAC> {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl<const FRAC: u32> SubAssign for Fixed<$base, FRAC> {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl<const FRAC: u32> MulAssign for Fixed<$base, FRAC> {
+            fn mul_assign(&mut self, rhs: Self) {
+                *self = *self * rhs;
+            }
+        }
+
+        impl<const FRAC: u32> DivAssign for Fixed<$base, FRAC> {
+            fn div_assign(&mut self, rhs: Self) {
+                *self = *self / rhs;
+            }
+        }
+
+        impl<const FRAC: u32> From<u8> for Fixed<$base, FRAC> {
+            fn from(value: u8) -> Self {
+                Self::from_int(value.into())
+            }
+        }
+
+        impl<const FRAC: u32> Display for Fixed<$base, FRAC> {
+            fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+                write!(f, "{}", self.to_f64())
+            }
+        }
+
+        impl<const FRAC: u32> VectorType for Fixed<$base, FRAC> {}
+    };
+}
+
+define_fixed!(i32, i64);
+define_fixed!(i64, i128);
+
+#[cfg(test)]
+mod tests {
+    use super::I16F16;
+    use super::I32F32;
+    use crate::math::matrix::Matrix;
+    use crate::math::vector::Dot;
+    use crate::math::vector::Vector2;
+
+    #[test]
+    fn test_fixed_arithmetic() {
+        let a = I16F16::from_f64(2.5);
+        let b = I16F16::from_f64(-0.25);
+        assert_eq!(I16F16::from_f64(2.25), a + b);
+        assert_eq!(I16F16::from_f64(-0.625), a * b);
+        assert_eq!(I16F16::from_int(-10), a / b);
+        assert_eq!(I16F16::from_f64(1.5), I16F16::from_f64(2.25).sqrt());
+        assert_eq!(-1, b.to_int());
+        assert_eq!(I16F16::from_f64(0.5), a.fract());
+        assert_eq!(I16F16::from_f64(-0.25), b.fract());
+        assert_eq!(I16F16::ZERO, I16F16::from_int(-3).fract());
+    }
+
+    #[test]
+    fn test_fixed_overflow() {
+        let big = I16F16::from_int(30000);
+        assert_eq!(None, big.checked_add(big));
+        assert_eq!(None, big.checked_mul(big));
+        assert_eq!(I16F16::MAX, big.saturating_mul(big));
+        assert_eq!(I16F16::MIN, big.saturating_mul(-big));
+        assert_eq!(None, big.checked_div(I16F16::ZERO));
+    }
+
+    #[test]
+    #[should_panic(expected = "attempt to multiply with overflow")]
+    fn test_fixed_mul_overflow_panics() {
+        let big = I16F16::from_int(30000);
+        let _ = big * big;
+    }
+
+    #[test]
+    fn test_fixed_trigonometry() {
+        for i in -40..40 {
+            let angle = i as f64 * 0.37;
+            let fixed = I32F32::from_f64(angle);
+            assert!((fixed.sin().to_f64() - angle.sin()).abs() < 1e-5);
+            assert!((fixed.cos().to_f64() - angle.cos()).abs() < 1e-5);
+
+            let x = I32F32::from_f64(angle.cos());
+            let y = I32F32::from_f64(angle.sin());
+            assert!((y.atan2(x).to_f64() - angle.sin().atan2(angle.cos())).abs() < 1e-4);
+        }
+
+        let half_pi = I16F16::from_bits(I16F16::pi().to_bits() / 2);
+        assert_eq!(I16F16::MAX, half_pi.tan());
+        assert_eq!(I16F16::MIN, (-half_pi).tan());
+        assert!((I16F16::from_f64(0.5).tan().to_f64() - 0.5f64.tan()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_fixed_vector_and_matrix() {
+        let v1 = Vector2::new(I16F16::from_f64(0.5), I16F16::from_int(2));
+        let v2 = Vector2::new(I16F16::from_int(4), I16F16::from_f64(0.25));
+        assert_eq!(I16F16::from_f64(2.5), v1.dot(v2));
+
+        let m = Matrix::new([
+            [I16F16::from_f64(1.5), I16F16::ZERO],
+            [I16F16::one(), I16F16::ZERO],
+        ]);
+        assert_eq!(m, &Matrix::<I16F16, 2, 2>::identity() * &m);
+    }
+}
@@ -0,0 +1,782 @@
+use super::matrix::Matrix4x4f;
+use super::vector::Cross;
+use super::vector::Dot;
+use super::vector::Vector3f;
+
+const EPSILON: f64 = 1e-9;
+
+pub trait Intersects<Rhs = Self> {
+    fn intersects(&self, rhs: &Rhs) -> bool;
+}
+
+pub trait RayCast {
+    /// Returns the distance along the ray to the first hit, if any.
+    fn ray_cast(&self, ray: &Ray) -> Option<f64>;
+}
+
+pub trait ClosestPoint {
+    fn closest_point(&self, point: &Vector3f) -> Vector3f;
+}
+
+fn to_array(v: &Vector3f) -> [f64; 3] {
+    [v.x, v.y, v.z]
+}
+
+fn from_array(a: [f64; 3]) -> Vector3f {
+    Vector3f::new(a[0], a[1], a[2])
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Ray {
+    pub origin: Vector3f,
+    pub direction: Vector3f,
+}
+
+impl Ray {
+    /// The direction is normalized, so ray cast results are distances.
+    pub fn new(origin: Vector3f, direction: Vector3f) -> Self {
+        Self {
+            origin,
+            direction: direction.normalized(),
+        }
+    }
+
+    pub fn point_at(&self, distance: f64) -> Vector3f {
+        self.origin + self.direction * distance
+    }
+}
+
+/// The set of points `p` satisfying `normal.dot(p) + distance == 0`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Plane {
+    pub normal: Vector3f,
+    pub distance: f64,
+}
+
+impl Plane {
+    pub fn new(normal: Vector3f, distance: f64) -> Self {
+        Self { normal, distance }
+    }
+
+    pub fn from_point_normal(point: &Vector3f, normal: &Vector3f) -> Self {
+        let normal = normal.normalized();
+        Self {
+            normal,
+            distance: -normal.dot(point),
+        }
+    }
+
+    /// The normal follows the counter-clockwise winding of `a`, `b`, `c`.
+    pub fn from_points(a: &Vector3f, b: &Vector3f, c: &Vector3f) -> Self {
+        Self::from_point_normal(a, &(b - a).cross(c - a))
+    }
+
+    pub fn normalized(&self) -> Self {
+        let length = self.normal.length();
+        Self {
+            normal: self.normal / length,
+            distance: self.distance / length,
+        }
+    }
+
+    pub fn signed_distance(&self, point: &Vector3f) -> f64 {
+        self.normal.dot(point) + self.distance
+    }
+}
+
+impl ClosestPoint for Plane {
+    fn closest_point(&self, point: &Vector3f) -> Vector3f {
+        point - self.normal * self.signed_distance(point)
+    }
+}
+
+impl RayCast for Plane {
+    fn ray_cast(&self, ray: &Ray) -> Option<f64> {
+        let denominator = self.normal.dot(ray.direction);
+        if denominator.abs() < EPSILON {
+            return None;
+        }
+
+        let t = -self.signed_distance(&ray.origin) / denominator;
+        (t >= 0.0).then_some(t)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Aabb {
+    pub min: Vector3f,
+    pub max: Vector3f,
+}
+
+impl Aabb {
+    pub fn new(min: Vector3f, max: Vector3f) -> Self {
+        Self { min, max }
+    }
+
+    pub fn from_center_extents(center: &Vector3f, half_extents: &Vector3f) -> Self {
+        Self {
+            min: center - half_extents,
+            max: center + half_extents,
+        }
+    }
+
+    pub fn from_points<'a, T: IntoIterator<Item = &'a Vector3f>>(points: T) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = *points.next()?;
+
+        Some(points.fold(Self::new(first, first), |aabb, point| {
+            Self::new(aabb.min.min(point), aabb.max.max(point))
+        }))
+    }
+
+    pub fn center(&self) -> Vector3f {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn half_extents(&self) -> Vector3f {
+        (self.max - self.min) * 0.5
+    }
+
+    pub fn contains(&self, point: &Vector3f) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x)
+            && (self.min.y..=self.max.y).contains(&point.y)
+            && (self.min.z..=self.max.z).contains(&point.z)
+    }
+
+    pub fn merge(&self, rhs: &Aabb) -> Self {
+        Self::new(self.min.min(&rhs.min), self.max.max(&rhs.max))
+    }
+}
+
+impl ClosestPoint for Aabb {
+    fn closest_point(&self, point: &Vector3f) -> Vector3f {
+        point.max(&self.min).min(&self.max)
+    }
+}
+
+impl RayCast for Aabb {
+    fn ray_cast(&self, ray: &Ray) -> Option<f64> {
+        let origin = to_array(&ray.origin);
+        let direction = to_array(&ray.direction);
+        let min = to_array(&self.min);
+        let max = to_array(&self.max);
+
+        let mut t_min: f64 = 0.0;
+        let mut t_max = f64::INFINITY;
+        for i in 0..3 {
+            if direction[i].abs() < EPSILON {
+                if origin[i] < min[i] || origin[i] > max[i] {
+                    return None;
+                }
+            } else {
+                let inverse = 1.0 / direction[i];
+                let t1 = (min[i] - origin[i]) * inverse;
+                let t2 = (max[i] - origin[i]) * inverse;
+                t_min = t_min.max(t1.min(t2));
+                t_max = t_max.min(t1.max(t2));
+                if t_min > t_max {
+                    return None;
+                }
+            }
+        }
+
+        Some(t_min)
+    }
+}
+
+impl Intersects for Aabb {
+    fn intersects(&self, rhs: &Aabb) -> bool {
+        self.min.x <= rhs.max.x
+            && self.max.x >= rhs.min.x
+            && self.min.y <= rhs.max.y
+            && self.max.y >= rhs.min.y
+            && self.min.z <= rhs.max.z
+            && self.max.z >= rhs.min.z
+    }
+}
+
+impl Intersects<Sphere> for Aabb {
+    fn intersects(&self, rhs: &Sphere) -> bool {
+        rhs.intersects(self)
+    }
+}
+
+impl Intersects<Plane> for Aabb {
+    fn intersects(&self, rhs: &Plane) -> bool {
+        let half_extents = self.half_extents();
+        let radius = half_extents.x * rhs.normal.x.abs()
+            + half_extents.y * rhs.normal.y.abs()
+            + half_extents.z * rhs.normal.z.abs();
+
+        rhs.signed_distance(&self.center()).abs() <= radius
+    }
+}
+
+impl Intersects<Obb> for Aabb {
+    fn intersects(&self, rhs: &Obb) -> bool {
+        Obb::from(self).intersects(rhs)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Sphere {
+    pub center: Vector3f,
+    pub radius: f64,
+}
+
+impl Sphere {
+    pub fn new(center: Vector3f, radius: f64) -> Self {
+        Self { center, radius }
+    }
+
+    pub fn contains(&self, point: &Vector3f) -> bool {
+        (point - self.center).length_squared() <= self.radius * self.radius
+    }
+}
+
+impl ClosestPoint for Sphere {
+    fn closest_point(&self, point: &Vector3f) -> Vector3f {
+        let offset = point - self.center;
+        let distance = offset.length();
+        if distance <= self.radius {
+            *point
+        } else {
+            self.center + offset * (self.radius / distance)
+        }
+    }
+}
+
+impl RayCast for Sphere {
+    fn ray_cast(&self, ray: &Ray) -> Option<f64> {
+        let offset = ray.origin - self.center;
+        let b = offset.dot(ray.direction);
+        let c = offset.length_squared() - self.radius * self.radius;
+        if c > 0.0 && b > 0.0 {
+            return None;
+        }
+
+        let discriminant = b * b - c;
+        if discriminant < 0.0 {
+            return None;
+        }
+
+        Some((-b - discriminant.sqrt()).max(0.0))
+    }
+}
+
+impl Intersects for Sphere {
+    fn intersects(&self, rhs: &Sphere) -> bool {
+        let radius = self.radius + rhs.radius;
+        (self.center - rhs.center).length_squared() <= radius * radius
+    }
+}
+
+impl Intersects<Aabb> for Sphere {
+    fn intersects(&self, rhs: &Aabb) -> bool {
+        self.contains(&rhs.closest_point(&self.center))
+    }
+}
+
+impl Intersects<Obb> for Sphere {
+    fn intersects(&self, rhs: &Obb) -> bool {
+        self.contains(&rhs.closest_point(&self.center))
+    }
+}
+
+impl Intersects<Plane> for Sphere {
+    fn intersects(&self, rhs: &Plane) -> bool {
+        rhs.signed_distance(&self.center).abs() <= self.radius
+    }
+}
+
+impl Intersects<Triangle> for Sphere {
+    fn intersects(&self, rhs: &Triangle) -> bool {
+        self.contains(&rhs.closest_point(&self.center))
+    }
+}
+
+/// An oriented bounding box. The `axes` are expected to be orthonormal.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Obb {
+    pub center: Vector3f,
+    pub axes: [Vector3f; 3],
+    pub half_extents: Vector3f,
+}
+
+impl Obb {
+    pub fn new(center: Vector3f, axes: [Vector3f; 3], half_extents: Vector3f) -> Self {
+        Self {
+            center,
+            axes,
+            half_extents,
+        }
+    }
+
+    pub fn contains(&self, point: &Vector3f) -> bool {
+        let offset = point - self.center;
+        self.axes
+            .iter()
+            .zip(to_array(&self.half_extents))
+            .all(|(axis, half_extent)| offset.dot(axis).abs() <= half_extent)
+    }
+}
+
+impl From<&Aabb> for Obb {
+    fn from(value: &Aabb) -> Self {
+        Self::new(
+            value.center(),
+            [
+                Vector3f::new(1.0, 0.0, 0.0),
+                Vector3f::new(0.0, 1.0, 0.0),
+                Vector3f::new(0.0, 0.0, 1.0),
+            ],
+            value.half_extents(),
+        )
+    }
+}
+
+impl ClosestPoint for Obb {
+    fn closest_point(&self, point: &Vector3f) -> Vector3f {
+        let offset = point - self.center;
+        self.axes.iter().zip(to_array(&self.half_extents)).fold(
+            self.center,
+            |closest, (axis, half_extent)| {
+                closest + axis * offset.dot(axis).clamp(-half_extent, half_extent)
+            },
+        )
+    }
+}
+
+impl RayCast for Obb {
+    fn ray_cast(&self, ray: &Ray) -> Option<f64> {
+        let offset = ray.origin - self.center;
+        let local_ray = Ray {
+            origin: from_array(self.axes.map(|axis| offset.dot(axis))),
+            direction: from_array(self.axes.map(|axis| ray.direction.dot(axis))),
+        };
+
+        Aabb::new(-self.half_extents, self.half_extents).ray_cast(&local_ray)
+    }
+}
+
+impl Intersects for Obb {
+    fn intersects(&self, rhs: &Obb) -> bool {
+        let a = to_array(&self.half_extents);
+        let b = to_array(&rhs.half_extents);
+
+        let mut rotation = [[0.0; 3]; 3];
+        let mut abs_rotation = [[0.0; 3]; 3];
+        for i in 0..3 {
+            for j in 0..3 {
+                rotation[i][j] = self.axes[i].dot(rhs.axes[j]);
+                abs_rotation[i][j] = rotation[i][j].abs() + EPSILON;
+            }
+        }
+
+        let offset = rhs.center - self.center;
+        let t = self.axes.map(|axis| offset.dot(axis));
+
+        for i in 0..3 {
+            let ra = a[i];
+            let rb = (0..3).map(|j| b[j] * abs_rotation[i][j]).sum::<f64>();
+            if t[i].abs() > ra + rb {
+                return false;
+            }
+        }
+
+        for j in 0..3 {
+            let ra = (0..3).map(|i| a[i] * abs_rotation[i][j]).sum::<f64>();
+            let rb = b[j];
+            let t = (0..3).map(|i| t[i] * rotation[i][j]).sum::<f64>();
+            if t.abs() > ra + rb {
+                return false;
+            }
+        }
+
+        for i in 0..3 {
+            let (i1, i2) = ((i + 1) % 3, (i + 2) % 3);
+            for j in 0..3 {
+                let (j1, j2) = ((j + 1) % 3, (j + 2) % 3);
+                let ra = a[i1] * abs_rotation[i2][j] + a[i2] * abs_rotation[i1][j];
+                let rb = b[j1] * abs_rotation[i][j2] + b[j2] * abs_rotation[i][j1];
+                let t = t[i2] * rotation[i1][j] - t[i1] * rotation[i2][j];
+                if t.abs() > ra + rb {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+impl Intersects<Aabb> for Obb {
+    fn intersects(&self, rhs: &Aabb) -> bool {
+        self.intersects(&Obb::from(rhs))
+    }
+}
+
+impl Intersects<Sphere> for Obb {
+    fn intersects(&self, rhs: &Sphere) -> bool {
+        rhs.intersects(self)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Segment {
+    pub start: Vector3f,
+    pub end: Vector3f,
+}
+
+impl Segment {
+    pub fn new(start: Vector3f, end: Vector3f) -> Self {
+        Self { start, end }
+    }
+
+    pub fn length(&self) -> f64 {
+        self.start.distance(&self.end)
+    }
+
+    /// Returns the pair of closest points, the first lying on `self` and the
+    /// second on `rhs`.
+    pub fn closest_points(&self, rhs: &Segment) -> (Vector3f, Vector3f) {
+        let d1 = self.end - self.start;
+        let d2 = rhs.end - rhs.start;
+        let r = self.start - rhs.start;
+        let a = d1.length_squared();
+        let e = d2.length_squared();
+        let f = d2.dot(r);
+
+        let (s, t) = if a < EPSILON && e < EPSILON {
+            (0.0, 0.0)
+        } else if a < EPSILON {
+            (0.0, (f / e).clamp(0.0, 1.0))
+        } else {
+            let c = d1.dot(r);
+            if e < EPSILON {
+                ((-c / a).clamp(0.0, 1.0), 0.0)
+            } else {
+                let b = d1.dot(d2);
+                let denominator = a * e - b * b;
+                let s = if denominator.abs() > EPSILON {
+                    ((b * f - c * e) / denominator).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+
+                let t = (b * s + f) / e;
+                if t < 0.0 {
+                    ((-c / a).clamp(0.0, 1.0), 0.0)
+                } else if t > 1.0 {
+                    (((b - c) / a).clamp(0.0, 1.0), 1.0)
+                } else {
+                    (s, t)
+                }
+            }
+        };
+
+        (self.start + d1 * s, rhs.start + d2 * t)
+    }
+}
+
+impl ClosestPoint for Segment {
+    fn closest_point(&self, point: &Vector3f) -> Vector3f {
+        let direction = self.end - self.start;
+        let length_squared = direction.length_squared();
+        if length_squared < EPSILON {
+            return self.start;
+        }
+
+        let t = ((point - self.start).dot(direction) / length_squared).clamp(0.0, 1.0);
+        self.start + direction * t
+    }
+}
+
+impl Intersects<Sphere> for Segment {
+    fn intersects(&self, rhs: &Sphere) -> bool {
+        rhs.contains(&self.closest_point(&rhs.center))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Triangle {
+    pub a: Vector3f,
+    pub b: Vector3f,
+    pub c: Vector3f,
+}
+
+impl Triangle {
+    pub fn new(a: Vector3f, b: Vector3f, c: Vector3f) -> Self {
+        Self { a, b, c }
+    }
+
+    pub fn normal(&self) -> Vector3f {
+        (self.b - self.a).cross(self.c - self.a).normalized()
+    }
+
+    pub fn plane(&self) -> Plane {
+        Plane::from_points(&self.a, &self.b, &self.c)
+    }
+}
+
+impl ClosestPoint for Triangle {
+    fn closest_point(&self, point: &Vector3f) -> Vector3f {
+        let ab = self.b - self.a;
+        let ac = self.c - self.a;
+
+        let ap = point - self.a;
+        let d1 = ab.dot(ap);
+        let d2 = ac.dot(ap);
+        if d1 <= 0.0 && d2 <= 0.0 {
+            return self.a;
+        }
+
+        let bp = point - self.b;
+        let d3 = ab.dot(bp);
+        let d4 = ac.dot(bp);
+        if d3 >= 0.0 && d4 <= d3 {
+            return self.b;
+        }
+
+        let vc = d1 * d4 - d3 * d2;
+        if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+            return self.a + ab * (d1 / (d1 - d3));
+        }
+
+        let cp = point - self.c;
+        let d5 = ab.dot(cp);
+        let d6 = ac.dot(cp);
+        if d6 >= 0.0 && d5 <= d6 {
+            return self.c;
+        }
+
+        let vb = d5 * d2 - d1 * d6;
+        if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+            return self.a + ac * (d2 / (d2 - d6));
+        }
+
+        let va = d3 * d6 - d5 * d4;
+        if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+            return self.b + (self.c - self.b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+        }
+
+        let denominator = 1.0 / (va + vb + vc);
+        self.a + ab * (vb * denominator) + ac * (vc * denominator)
+    }
+}
+
+impl RayCast for Triangle {
+    fn ray_cast(&self, ray: &Ray) -> Option<f64> {
+        let edge1 = self.b - self.a;
+        let edge2 = self.c - self.a;
+        let p = ray.direction.cross(edge2);
+        let determinant = edge1.dot(p);
+        if determinant.abs() < EPSILON {
+            return None;
+        }
+
+        let inverse = 1.0 / determinant;
+        let s = ray.origin - self.a;
+        let u = s.dot(p) * inverse;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = s.cross(edge1);
+        let v = ray.direction.dot(q) * inverse;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t = edge2.dot(q) * inverse;
+        (t >= 0.0).then_some(t)
+    }
+}
+
+impl Intersects<Sphere> for Triangle {
+    fn intersects(&self, rhs: &Sphere) -> bool {
+        rhs.intersects(self)
+    }
+}
+
+/// Six inward-facing planes in the order left, right, bottom, top, near, far.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Frustum {
+    pub planes: [Plane; 6],
+}
+
+impl Frustum {
+    pub fn new(planes: [Plane; 6]) -> Self {
+        Self { planes }
+    }
+
+    /// Extracts the planes of a (view-)projection matrix that maps points,
+    /// treated as column vectors, into OpenGL clip space.
+    pub fn from_matrix(matrix: &Matrix4x4f) -> Self {
+        let row = |i: usize| matrix.elements[i];
+        let plane = |lhs: [f64; 4], rhs: [f64; 4], sign: f64| {
+            Plane::new(
+                Vector3f::new(
+                    lhs[0] + sign * rhs[0],
+                    lhs[1] + sign * rhs[1],
+                    lhs[2] + sign * rhs[2],
+                ),
+                lhs[3] + sign * rhs[3],
+            )
+            .normalized()
+        };
+
+        Self::new([
+            plane(row(3), row(0), 1.0),
+            plane(row(3), row(0), -1.0),
+            plane(row(3), row(1), 1.0),
+            plane(row(3), row(1), -1.0),
+            plane(row(3), row(2), 1.0),
+            plane(row(3), row(2), -1.0),
+        ])
+    }
+
+    pub fn contains(&self, point: &Vector3f) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.signed_distance(point) >= 0.0)
+    }
+}
+
+impl Intersects<Sphere> for Frustum {
+    fn intersects(&self, rhs: &Sphere) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.signed_distance(&rhs.center) >= -rhs.radius)
+    }
+}
+
+/// Conservative: boxes close to a frustum corner may be reported as
+/// intersecting.
+impl Intersects<Aabb> for Frustum {
+    fn intersects(&self, rhs: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            let positive_vertex = Vector3f::new(
+                if plane.normal.x >= 0.0 {
+                    rhs.max.x
+                } else {
+                    rhs.min.x
+                },
+                if plane.normal.y >= 0.0 {
+                    rhs.max.y
+                } else {
+                    rhs.min.y
+                },
+                if plane.normal.z >= 0.0 {
+                    rhs.max.z
+                } else {
+                    rhs.min.z
+                },
+            );
+
+            plane.signed_distance(&positive_vertex) >= 0.0
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Aabb;
+    use super::ClosestPoint;
+    use super::Frustum;
+    use super::Intersects;
+    use super::Obb;
+    use super::Ray;
+    use super::RayCast;
+    use super::Segment;
+    use super::Sphere;
+    use super::Triangle;
+    use crate::math::matrix::Matrix4x4f;
+    use crate::math::vector::Vector3f;
+
+    fn unit_aabb() -> Aabb {
+        Aabb::new(
+            Vector3f::new(-1.0, -1.0, -1.0),
+            Vector3f::new(1.0, 1.0, 1.0),
+        )
+    }
+
+    #[test]
+    fn test_ray_cast() {
+        let ray = Ray::new(Vector3f::new(-5.0, 0.0, 0.0), Vector3f::new(2.0, 0.0, 0.0));
+        assert_eq!(Some(4.0), unit_aabb().ray_cast(&ray));
+        assert_eq!(
+            Some(4.0),
+            Sphere::new(Vector3f::default(), 1.0).ray_cast(&ray)
+        );
+
+        let triangle = Triangle::new(
+            Vector3f::new(0.0, -1.0, -1.0),
+            Vector3f::new(0.0, 1.0, -1.0),
+            Vector3f::new(0.0, 0.0, 1.0),
+        );
+        assert_eq!(Some(5.0), triangle.ray_cast(&ray));
+
+        let miss = Ray::new(Vector3f::new(-5.0, 2.0, 0.0), Vector3f::new(1.0, 0.0, 0.0));
+        assert_eq!(None, unit_aabb().ray_cast(&miss));
+    }
+
+    #[test]
+    fn test_overlap() {
+        let sphere = Sphere::new(Vector3f::new(1.5, 0.0, 0.0), 1.0);
+        assert!(unit_aabb().intersects(&sphere));
+        assert!(!unit_aabb().intersects(&Sphere::new(Vector3f::new(3.0, 0.0, 0.0), 1.0)));
+
+        let s = std::f64::consts::FRAC_1_SQRT_2;
+        let rotated = Obb::new(
+            Vector3f::new(2.3, 0.0, 0.0),
+            [
+                Vector3f::new(s, s, 0.0),
+                Vector3f::new(-s, s, 0.0),
+                Vector3f::new(0.0, 0.0, 1.0),
+            ],
+            Vector3f::new(1.0, 1.0, 1.0),
+        );
+        assert!(unit_aabb().intersects(&rotated));
+        assert!(!Obb::from(&unit_aabb()).intersects(&Obb {
+            center: Vector3f::new(2.5, 0.0, 0.0),
+            ..rotated
+        }));
+    }
+
+    #[test]
+    fn test_closest_point() {
+        let point = Vector3f::new(3.0, 0.5, -4.0);
+        assert_eq!(
+            Vector3f::new(1.0, 0.5, -1.0),
+            unit_aabb().closest_point(&point)
+        );
+
+        let segment = Segment::new(Vector3f::new(0.0, 0.0, 0.0), Vector3f::new(0.0, 0.0, 2.0));
+        let other = Segment::new(Vector3f::new(1.0, -1.0, 1.0), Vector3f::new(1.0, 1.0, 1.0));
+        assert_eq!(
+            (Vector3f::new(0.0, 0.0, 1.0), Vector3f::new(1.0, 0.0, 1.0)),
+            segment.closest_points(&other)
+        );
+    }
+
+    #[test]
+    fn test_frustum_from_matrix() {
+        let orthographic = Matrix4x4f::new([
+            [0.5, 0.0, 0.0, 0.0],
+            [0.0, 0.5, 0.0, 0.0],
+            [0.0, 0.0, -0.2, -1.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        let frustum = Frustum::from_matrix(&orthographic);
+
+        assert!(frustum.contains(&Vector3f::new(1.0, -1.0, -5.0)));
+        assert!(!frustum.contains(&Vector3f::new(3.0, 0.0, -5.0)));
+        assert!(!frustum.contains(&Vector3f::new(0.0, 0.0, 1.0)));
+        assert!(frustum.intersects(&Sphere::new(Vector3f::new(2.5, 0.0, -5.0), 1.0)));
+        assert!(!frustum.intersects(&Aabb::new(
+            Vector3f::new(0.0, 0.0, -20.0),
+            Vector3f::new(1.0, 1.0, -11.0)
+        )));
+    }
+}
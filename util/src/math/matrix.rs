@@ -8,6 +8,7 @@ use std::ops::Neg;
 use std::ops::Sub;
 use std::ops::SubAssign;
 
+use super::vector::Vector3f;
 use super::vector::VectorType;
 use crate::forward_ref_binop;
 use crate::forward_ref_binop_assign;
@@ -155,6 +156,82 @@ impl<T: VectorType + Default, const N: usize, const M: usize> Div<&T> for &Matri
 forward_ref_binop!(impl [T: VectorType + Default, const N: usize, const M: usize] Div, div for Matrix<T, N, M>, T);
 forward_ref_binop_assign!(impl [T: VectorType + Default, const N: usize, const M: usize] Div, div, DivAssign, div_assign for Matrix<T, N, M>, T);
 
+/// Affine transforms treat points as column vectors, so `a * b` applies `b`
+/// first.
+impl Matrix<f64, 4, 4> {
+    pub fn translation(translation: &Vector3f) -> Self {
+        Self::new([
+            [1.0, 0.0, 0.0, translation.x],
+            [0.0, 1.0, 0.0, translation.y],
+            [0.0, 0.0, 1.0, translation.z],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn scale(scale: &Vector3f) -> Self {
+        Self::new([
+            [scale.x, 0.0, 0.0, 0.0],
+            [0.0, scale.y, 0.0, 0.0],
+            [0.0, 0.0, scale.z, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn rotation_x(angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::new([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, cos, -sin, 0.0],
+            [0.0, sin, cos, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn rotation_y(angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::new([
+            [cos, 0.0, sin, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [-sin, 0.0, cos, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn rotation_z(angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::new([
+            [cos, -sin, 0.0, 0.0],
+            [sin, cos, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Rotates around x, then y, then z, by the components of `angles` in
+    /// radians.
+    pub fn rotation(angles: &Vector3f) -> Self {
+        Self::rotation_z(angles.z) * Self::rotation_y(angles.y) * Self::rotation_x(angles.x)
+    }
+
+    pub fn transform_point(&self, point: &Vector3f) -> Vector3f {
+        let e = &self.elements;
+        Vector3f::new(
+            e[0][0] * point.x + e[0][1] * point.y + e[0][2] * point.z + e[0][3],
+            e[1][0] * point.x + e[1][1] * point.y + e[1][2] * point.z + e[1][3],
+            e[2][0] * point.x + e[2][1] * point.y + e[2][2] * point.z + e[2][3],
+        )
+    }
+
+    pub fn transform_vector(&self, vector: &Vector3f) -> Vector3f {
+        let e = &self.elements;
+        Vector3f::new(
+            e[0][0] * vector.x + e[0][1] * vector.y + e[0][2] * vector.z,
+            e[1][0] * vector.x + e[1][1] * vector.y + e[1][2] * vector.z,
+            e[2][0] * vector.x + e[2][1] * vector.y + e[2][2] * vector.z,
+        )
+    }
+}
+
 pub type Matrix2x2i = Matrix<isize, 2, 2>;
 pub type Matrix3x3i = Matrix<isize, 3, 3>;
 pub type Matrix4x4i = Matrix<isize, 4, 4>;
@@ -165,6 +242,8 @@ pub type Matrix4x4f = Matrix<f64, 4, 4>;
 #[cfg(test)]
 mod tests {
     use super::Matrix2x2i;
+    use super::Matrix4x4f;
+    use crate::math::vector::Vector3f;
 
     #[test]
     fn test_matrix_add() {
@@ -173,6 +252,16 @@ mod tests {
         assert_eq!(Matrix2x2i::new([[3, 3], [7, 7]]), lhs + rhs);
     }
 
+    #[test]
+    fn test_matrix_transform_point() {
+        let transform = Matrix4x4f::translation(&Vector3f::new(1.0, 2.0, 3.0))
+            * Matrix4x4f::scale(&Vector3f::new(2.0, 2.0, 2.0));
+        assert_eq!(
+            Vector3f::new(3.0, 4.0, 5.0),
+            transform.transform_point(&Vector3f::new(1.0, 1.0, 1.0))
+        );
+    }
+
     #[test]
     fn test_matrix_mul() {
         let lhs = Matrix2x2i::new([[1, 2], [3, 4]]);
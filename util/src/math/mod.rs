@@ -1,2 +1,6 @@
+pub mod curve;
+pub mod easing;
+pub mod fixed;
+pub mod geometry;
 pub mod matrix;
 pub mod vector;
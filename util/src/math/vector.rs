@@ -54,6 +54,32 @@ macro_rules! strip_plus {
     }
 }
 
+macro_rules! define_float_vector {
+    ($name:ident, $type:ty) => {
+        impl $name<$type> {
+            pub fn length_squared(&self) -> $type {
+                self.dot(self)
+            }
+
+            pub fn length(&self) -> $type {
+                self.length_squared().sqrt()
+            }
+
+            pub fn normalized(&self) -> Self {
+                self / self.length()
+            }
+
+            pub fn distance(&self, rhs: &$name<$type>) -> $type {
+                (self - rhs).length()
+            }
+
+            pub fn lerp(&self, rhs: &$name<$type>, t: $type) -> Self {
+                self + (rhs - self) * t
+            }
+        }
+    };
+}
+
 macro_rules! define_vector {
     ($name:ident $(, $component:ident)+) => {
         #[derive(Clone, Copy, PartialEq, Debug)]
@@ -162,6 +188,19 @@ macro_rules! define_vector {
         forward_ref_binop!(impl [T: VectorType] Div, div for $name<T>, T);
         forward_ref_binop_assign!(impl [T: VectorType]  Div, div, DivAssign, div_assign for $name<T>, T);
 
+        impl<T: VectorType + PartialOrd> $name<T> {
+            pub fn min(&self, rhs: &$name<T>) -> Self {
+                $name::<T>::new($(if self.$component < rhs.$component { self.$component } else { rhs.$component }),*)
+            }
+
+            pub fn max(&self, rhs: &$name<T>) -> Self {
+                $name::<T>::new($(if self.$component > rhs.$component { self.$component } else { rhs.$component }),*)
+            }
+        }
+
+        define_float_vector!($name, f32);
+        define_float_vector!($name, f64);
+
         impl<T, U: Into<T> + Copy> From<&$name<U>> for $name<T> {
             fn from(value: &$name<U>) -> Self {
                 $name::<T>::new($(value.$component.into()),*)
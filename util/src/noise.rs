@@ -0,0 +1,359 @@
+use crate::math::vector::Vector2f;
+use crate::math::vector::Vector3f;
+use crate::random::Random;
+
+pub trait Noise {
+    fn sample2(&self, point: &Vector2f) -> f64;
+    fn sample3(&self, point: &Vector3f) -> f64;
+}
+
+/// A seeded permutation of `0..256`, repeated so lookups never wrap.
+#[derive(Clone)]
+struct PermutationTable {
+    values: [u8; 512],
+}
+
+impl PermutationTable {
+    fn new(seed: u64) -> Self {
+        let mut permutation: Vec<u8> = (0..=255).collect();
+        Random::new(seed).shuffle(&mut permutation);
+
+        let mut values = [0; 512];
+        for (i, value) in values.iter_mut().enumerate() {
+            *value = permutation[i & 255];
+        }
+
+        Self { values }
+    }
+
+    fn hash2(&self, x: i64, y: i64) -> u8 {
+        let x = (x & 255) as usize;
+        let y = (y & 255) as usize;
+        self.values[self.values[x] as usize + y]
+    }
+
+    fn hash3(&self, x: i64, y: i64, z: i64) -> u8 {
+        let z = (z & 255) as usize;
+        self.values[self.hash2(x, y) as usize + z]
+    }
+}
+
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+/// Random values on the integer lattice, smoothly interpolated. Output is in
+/// `[-1, 1]`.
+#[derive(Clone)]
+pub struct ValueNoise {
+    permutation_table: PermutationTable,
+}
+
+impl ValueNoise {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            permutation_table: PermutationTable::new(seed),
+        }
+    }
+
+    fn value(hash: u8) -> f64 {
+        hash as f64 / 127.5 - 1.0
+    }
+}
+
+impl Noise for ValueNoise {
+    fn sample2(&self, point: &Vector2f) -> f64 {
+        let (x0, y0) = (point.x.floor(), point.y.floor());
+        let (u, v) = (fade(point.x - x0), fade(point.y - y0));
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        let value = |dx, dy| Self::value(self.permutation_table.hash2(x0 + dx, y0 + dy));
+
+        lerp(
+            lerp(value(0, 0), value(1, 0), u),
+            lerp(value(0, 1), value(1, 1), u),
+            v,
+        )
+    }
+
+    fn sample3(&self, point: &Vector3f) -> f64 {
+        let (x0, y0, z0) = (point.x.floor(), point.y.floor(), point.z.floor());
+        let (u, v, w) = (fade(point.x - x0), fade(point.y - y0), fade(point.z - z0));
+        let (x0, y0, z0) = (x0 as i64, y0 as i64, z0 as i64);
+        let value =
+            |dx, dy, dz| Self::value(self.permutation_table.hash3(x0 + dx, y0 + dy, z0 + dz));
+
+        lerp(
+            lerp(
+                lerp(value(0, 0, 0), value(1, 0, 0), u),
+                lerp(value(0, 1, 0), value(1, 1, 0), u),
+                v,
+            ),
+            lerp(
+                lerp(value(0, 0, 1), value(1, 0, 1), u),
+                lerp(value(0, 1, 1), value(1, 1, 1), u),
+                v,
+            ),
+            w,
+        )
+    }
+}
+
+/// Ken Perlin's improved gradient noise. Output is roughly in `[-1, 1]` and
+/// zero on the integer lattice.
+#[derive(Clone)]
+pub struct PerlinNoise {
+    permutation_table: PermutationTable,
+}
+
+impl PerlinNoise {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            permutation_table: PermutationTable::new(seed),
+        }
+    }
+
+    fn gradient2(hash: u8, x: f64, y: f64) -> f64 {
+        match hash & 7 {
+            0 => x + y,
+            1 => -x + y,
+            2 => x - y,
+            3 => -x - y,
+            4 => x,
+            5 => -x,
+            6 => y,
+            _ => -y,
+        }
+    }
+
+    fn gradient3(hash: u8, x: f64, y: f64, z: f64) -> f64 {
+        let hash = hash & 15;
+        let u = if hash < 8 { x } else { y };
+        let v = match hash {
+            0..=3 => y,
+            12 | 14 => x,
+            _ => z,
+        };
+
+        (if hash & 1 == 0 { u } else { -u }) + (if hash & 2 == 0 { v } else { -v })
+    }
+}
+
+impl Noise for PerlinNoise {
+    fn sample2(&self, point: &Vector2f) -> f64 {
+        let (x0, y0) = (point.x.floor(), point.y.floor());
+        let (x, y) = (point.x - x0, point.y - y0);
+        let (u, v) = (fade(x), fade(y));
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        let gradient = |dx: i64, dy: i64| {
+            Self::gradient2(
+                self.permutation_table.hash2(x0 + dx, y0 + dy),
+                x - dx as f64,
+                y - dy as f64,
+            )
+        };
+
+        lerp(
+            lerp(gradient(0, 0), gradient(1, 0), u),
+            lerp(gradient(0, 1), gradient(1, 1), u),
+            v,
+        )
+    }
+
+    fn sample3(&self, point: &Vector3f) -> f64 {
+        let (x0, y0, z0) = (point.x.floor(), point.y.floor(), point.z.floor());
+        let (x, y, z) = (point.x - x0, point.y - y0, point.z - z0);
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+        let (x0, y0, z0) = (x0 as i64, y0 as i64, z0 as i64);
+        let gradient = |dx: i64, dy: i64, dz: i64| {
+            Self::gradient3(
+                self.permutation_table.hash3(x0 + dx, y0 + dy, z0 + dz),
+                x - dx as f64,
+                y - dy as f64,
+                z - dz as f64,
+            )
+        };
+
+        lerp(
+            lerp(
+                lerp(gradient(0, 0, 0), gradient(1, 0, 0), u),
+                lerp(gradient(0, 1, 0), gradient(1, 1, 0), u),
+                v,
+            ),
+            lerp(
+                lerp(gradient(0, 0, 1), gradient(1, 0, 1), u),
+                lerp(gradient(0, 1, 1), gradient(1, 1, 1), u),
+                v,
+            ),
+            w,
+        )
+    }
+}
+
+/// Cellular noise: the distance to the nearest of one jittered feature point
+/// per unit cell. Output is at most `sqrt(2)` in 2D and `sqrt(3)` in 3D.
+#[derive(Clone)]
+pub struct WorleyNoise {
+    permutation_table: PermutationTable,
+}
+
+impl WorleyNoise {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            permutation_table: PermutationTable::new(seed),
+        }
+    }
+
+    fn offset(&self, hash: u8, salt: i64) -> f64 {
+        self.permutation_table.hash2(hash as i64, salt) as f64 / 256.0
+    }
+}
+
+impl Noise for WorleyNoise {
+    fn sample2(&self, point: &Vector2f) -> f64 {
+        let (x0, y0) = (point.x.floor() as i64, point.y.floor() as i64);
+
+        let mut nearest = f64::INFINITY;
+        for y in y0 - 1..=y0 + 1 {
+            for x in x0 - 1..=x0 + 1 {
+                let hash = self.permutation_table.hash2(x, y);
+                let feature = Vector2f::new(
+                    x as f64 + self.offset(hash, 0),
+                    y as f64 + self.offset(hash, 1),
+                );
+                nearest = nearest.min(feature.distance(point));
+            }
+        }
+
+        nearest
+    }
+
+    fn sample3(&self, point: &Vector3f) -> f64 {
+        let (x0, y0, z0) = (
+            point.x.floor() as i64,
+            point.y.floor() as i64,
+            point.z.floor() as i64,
+        );
+
+        let mut nearest = f64::INFINITY;
+        for z in z0 - 1..=z0 + 1 {
+            for y in y0 - 1..=y0 + 1 {
+                for x in x0 - 1..=x0 + 1 {
+                    let hash = self.permutation_table.hash3(x, y, z);
+                    let feature = Vector3f::new(
+                        x as f64 + self.offset(hash, 0),
+                        y as f64 + self.offset(hash, 1),
+                        z as f64 + self.offset(hash, 2),
+                    );
+                    nearest = nearest.min(feature.distance(point));
+                }
+            }
+        }
+
+        nearest
+    }
+}
+
+/// Fractal Brownian motion: sums `octaves` layers of `noise`, each at
+/// `lacunarity` times the frequency and `persistence` times the amplitude of
+/// the previous one. The result is normalized back to the range of `noise`.
+#[derive(Clone)]
+pub struct Fractal<N> {
+    noise: N,
+    octaves: u32,
+    lacunarity: f64,
+    persistence: f64,
+}
+
+impl<N: Noise> Fractal<N> {
+    pub fn new(noise: N, octaves: u32) -> Self {
+        Self {
+            noise,
+            octaves: octaves.max(1),
+            lacunarity: 2.0,
+            persistence: 0.5,
+        }
+    }
+
+    pub fn with_lacunarity(mut self, lacunarity: f64) -> Self {
+        self.lacunarity = lacunarity;
+        self
+    }
+
+    pub fn with_persistence(mut self, persistence: f64) -> Self {
+        self.persistence = persistence;
+        self
+    }
+
+    fn accumulate<F: Fn(f64) -> f64>(&self, sample: F) -> f64 {
+        let mut frequency = 1.0;
+        let mut amplitude = 1.0;
+        let mut total = 0.0;
+        let mut total_amplitude = 0.0;
+        for _ in 0..self.octaves {
+            total += sample(frequency) * amplitude;
+            total_amplitude += amplitude;
+            frequency *= self.lacunarity;
+            amplitude *= self.persistence;
+        }
+
+        total / total_amplitude
+    }
+}
+
+impl<N: Noise> Noise for Fractal<N> {
+    fn sample2(&self, point: &Vector2f) -> f64 {
+        self.accumulate(|frequency| self.noise.sample2(&(point * frequency)))
+    }
+
+    fn sample3(&self, point: &Vector3f) -> f64 {
+        self.accumulate(|frequency| self.noise.sample3(&(point * frequency)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::SQRT_2;
+
+    use super::Fractal;
+    use super::Noise;
+    use super::PerlinNoise;
+    use super::ValueNoise;
+    use super::WorleyNoise;
+    use crate::math::vector::Vector2f;
+    use crate::math::vector::Vector3f;
+
+    #[test]
+    fn test_noise_is_deterministic() {
+        let point = Vector3f::new(1.3, -7.25, 0.5);
+        assert_eq!(
+            PerlinNoise::new(3).sample3(&point),
+            PerlinNoise::new(3).sample3(&point)
+        );
+        assert_eq!(
+            WorleyNoise::new(3).sample3(&point),
+            WorleyNoise::new(3).sample3(&point)
+        );
+    }
+
+    #[test]
+    fn test_perlin_noise_is_zero_on_lattice() {
+        let noise = PerlinNoise::new(11);
+        assert_eq!(0.0, noise.sample2(&Vector2f::new(3.0, -4.0)));
+        assert_eq!(0.0, noise.sample3(&Vector3f::new(3.0, -4.0, 5.0)));
+    }
+
+    #[test]
+    fn test_noise_range() {
+        let value = Fractal::new(ValueNoise::new(5), 4);
+        let worley = WorleyNoise::new(5);
+        for i in 0..1000 {
+            let point = Vector2f::new(i as f64 * 0.137, i as f64 * -0.071);
+            assert!((-1.0..=1.0).contains(&value.sample2(&point)));
+            assert!((0.0..=SQRT_2).contains(&worley.sample2(&point)));
+        }
+    }
+}
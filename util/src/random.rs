@@ -0,0 +1,211 @@
+use std::f64::consts::TAU;
+use std::ops::Range;
+
+use crate::math::vector::Vector2f;
+use crate::math::vector::Vector3f;
+
+/// A xoshiro256** generator. The same seed always produces the same sequence
+/// on every platform, which keeps replays and procedural content stable.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Random {
+    state: [u64; 4],
+}
+
+fn split_mix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        let mut seed = seed;
+        Self {
+            state: [
+                split_mix64(&mut seed),
+                split_mix64(&mut seed),
+                split_mix64(&mut seed),
+                split_mix64(&mut seed),
+            ],
+        }
+    }
+
+    /// Derives an independent generator, e.g. one per subsystem, without
+    /// disturbing the sequences of other consumers of `self`.
+    pub fn fork(&mut self) -> Self {
+        Self::new(self.next_u64())
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self.state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.state[1] << 17;
+
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= t;
+        self.state[3] = self.state[3].rotate_left(45);
+
+        result
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// Uniform in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// Uniform in `[0, 1)`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 * (1.0 / (1u32 << 24) as f32)
+    }
+
+    pub fn next_bool(&mut self) -> bool {
+        self.next_u64() >> 63 == 1
+    }
+
+    /// Returns `true` with the given probability.
+    pub fn chance(&mut self, probability: f64) -> bool {
+        self.next_f64() < probability
+    }
+
+    /// Uniform in `[0, bound)` without modulo bias.
+    fn bounded_u64(&mut self, bound: u64) -> u64 {
+        let mut product = self.next_u64() as u128 * bound as u128;
+        if (product as u64) < bound {
+            let threshold = bound.wrapping_neg() % bound;
+            while (product as u64) < threshold {
+                product = self.next_u64() as u128 * bound as u128;
+            }
+        }
+
+        (product >> 64) as u64
+    }
+
+    pub fn range<T: RandomRange>(&mut self, range: Range<T>) -> T {
+        T::sample(self, range)
+    }
+
+    pub fn unit_vector2(&mut self) -> Vector2f {
+        let angle = self.next_f64() * TAU;
+        Vector2f::new(angle.cos(), angle.sin())
+    }
+
+    pub fn unit_vector3(&mut self) -> Vector3f {
+        let z: f64 = self.range(-1.0..1.0);
+        let angle = self.next_f64() * TAU;
+        let radius = (1.0 - z * z).sqrt();
+        Vector3f::new(radius * angle.cos(), radius * angle.sin(), z)
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            None
+        } else {
+            Some(&items[self.range(0..items.len())])
+        }
+    }
+
+    /// Picks an index with a probability proportional to its weight. Negative
+    /// weights count as zero.
+    pub fn weighted_index(&mut self, weights: &[f64]) -> Option<usize> {
+        let total: f64 = weights.iter().map(|weight| weight.max(0.0)).sum();
+        if total <= 0.0 {
+            return None;
+        }
+
+        let mut target = self.next_f64() * total;
+        for (index, weight) in weights.iter().enumerate() {
+            let weight = weight.max(0.0);
+            if target < weight {
+                return Some(index);
+            }
+            target -= weight;
+        }
+
+        weights.iter().rposition(|weight| *weight > 0.0)
+    }
+
+    pub fn weighted_choice<'a, T>(&mut self, items: &'a [(T, f64)]) -> Option<&'a T> {
+        let weights: Vec<f64> = items.iter().map(|(_, weight)| *weight).collect();
+        self.weighted_index(&weights).map(|index| &items[index].0)
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.range(0..i + 1));
+        }
+    }
+}
+
+pub trait RandomRange: Sized {
+    fn sample(random: &mut Random, range: Range<Self>) -> Self;
+}
+
+macro_rules! impl_random_range_int {
+    ($($type:ty),*) => {
+        $(impl RandomRange for $type {
+            fn sample(random: &mut Random, range: Range<Self>) -> Self {
+                assert!(range.start < range.end, "cannot sample an empty range");
+                let span = (range.end as i128 - range.start as i128) as u64;
+                (range.start as i128 + random.bounded_u64(span) as i128) as $type
+            }
+        })*
+    };
+}
+
+impl_random_range_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl RandomRange for f64 {
+    fn sample(random: &mut Random, range: Range<Self>) -> Self {
+        range.start + (range.end - range.start) * random.next_f64()
+    }
+}
+
+impl RandomRange for f32 {
+    fn sample(random: &mut Random, range: Range<Self>) -> Self {
+        range.start + (range.end - range.start) * random.next_f32()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Random;
+
+    #[test]
+    fn test_random_is_reproducible() {
+        let mut a = Random::new(42);
+        let mut b = Random::new(42);
+        let mut c = Random::new(43);
+
+        let sequence: Vec<u64> = (0..16).map(|_| a.next_u64()).collect();
+        assert_eq!(sequence, (0..16).map(|_| b.next_u64()).collect::<Vec<_>>());
+        assert_ne!(sequence, (0..16).map(|_| c.next_u64()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_random_range() {
+        let mut random = Random::new(7);
+        for _ in 0..1000 {
+            assert!((-3..5).contains(&random.range(-3..5)));
+            assert!((0.5..1.5).contains(&random.range(0.5..1.5)));
+            assert!((random.unit_vector3().length() - 1.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_weighted_choice() {
+        let mut random = Random::new(1);
+        let items = [("never", 0.0), ("always", 1.0)];
+        for _ in 0..100 {
+            assert_eq!(Some(&"always"), random.weighted_choice(&items));
+        }
+        assert_eq!(None, random.weighted_index(&[0.0, -1.0]));
+    }
+}
@@ -1,16 +1,32 @@
-use std::sync::atomic::AtomicUsize;
+use std::fmt::Display;
+use std::fmt::Formatter;
 
-static COUNTER: AtomicUsize = AtomicUsize::new(0);
-
-#[derive(PartialEq, Eq, PartialOrd, Ord)]
+/// A generational index. The index addresses a slot in a
+/// [`SlotMap`](crate::slot_map::SlotMap) and the generation tells apart
+/// successive occupants of that slot, so an id that outlived its value is
+/// detected instead of silently aliasing a newer one.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub struct RuntimeId {
-    value: usize,
+    index: u32,
+    generation: u32,
 }
 
 impl RuntimeId {
-    pub fn generate() -> Self {
-        Self {
-            value: COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
-        }
+    pub(crate) fn new(index: u32, generation: u32) -> Self {
+        Self { index, generation }
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+impl Display for RuntimeId {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}v{}", self.index, self.generation)
     }
 }
@@ -0,0 +1,154 @@
+use crate::runtime_id::RuntimeId;
+
+struct Slot<T> {
+    generation: u32,
+    value: Option<T>,
+}
+
+/// A container with O(1) insertion, removal and lookup that hands out
+/// [`RuntimeId`]s. Looking up an id whose value has been removed returns
+/// `None`, even if its slot has been reused since.
+pub struct SlotMap<T> {
+    slots: Vec<Slot<T>>,
+    free_indices: Vec<u32>,
+    len: usize,
+}
+
+impl<T> SlotMap<T> {
+    pub fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            free_indices: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, value: T) -> RuntimeId {
+        self.insert_with(|_| value)
+    }
+
+    /// Like [`insert`](Self::insert), for values that need to know their own
+    /// id.
+    pub fn insert_with<F: FnOnce(RuntimeId) -> T>(&mut self, fun: F) -> RuntimeId {
+        let index = self.free_indices.pop().unwrap_or_else(|| {
+            self.slots.push(Slot {
+                generation: 0,
+                value: None,
+            });
+            (self.slots.len() - 1) as u32
+        });
+
+        let slot = &mut self.slots[index as usize];
+        let id = RuntimeId::new(index, slot.generation);
+        slot.value = Some(fun(id));
+        self.len += 1;
+
+        id
+    }
+
+    pub fn remove(&mut self, id: RuntimeId) -> Option<T> {
+        let slot = self.slots.get_mut(id.index() as usize)?;
+        if slot.generation != id.generation() {
+            return None;
+        }
+
+        let value = slot.value.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free_indices.push(id.index());
+        self.len -= 1;
+
+        Some(value)
+    }
+
+    pub fn contains(&self, id: RuntimeId) -> bool {
+        self.get(id).is_some()
+    }
+
+    pub fn get(&self, id: RuntimeId) -> Option<&T> {
+        self.slots
+            .get(id.index() as usize)
+            .filter(|slot| slot.generation == id.generation())
+            .and_then(|slot| slot.value.as_ref())
+    }
+
+    pub fn get_mut(&mut self, id: RuntimeId) -> Option<&mut T> {
+        self.slots
+            .get_mut(id.index() as usize)
+            .filter(|slot| slot.generation == id.generation())
+            .and_then(|slot| slot.value.as_mut())
+    }
+
+    /// Iterates in slot order, which is deterministic for a given sequence of
+    /// insertions and removals.
+    pub fn iter(&self) -> impl Iterator<Item = (RuntimeId, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            slot.value
+                .as_ref()
+                .map(|value| (RuntimeId::new(index as u32, slot.generation), value))
+        })
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (RuntimeId, &mut T)> {
+        self.slots
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, slot)| {
+                let generation = slot.generation;
+                slot.value
+                    .as_mut()
+                    .map(|value| (RuntimeId::new(index as u32, generation), value))
+            })
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = RuntimeId> + '_ {
+        self.iter().map(|(id, _)| id)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.iter().map(|(_, value)| value)
+    }
+}
+
+impl<T> Default for SlotMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SlotMap;
+
+    #[test]
+    fn test_slot_map() {
+        let mut slot_map = SlotMap::new();
+        let a = slot_map.insert("a");
+        let b = slot_map.insert("b");
+        assert_eq!(Some(&"a"), slot_map.get(a));
+        assert_eq!(2, slot_map.len());
+
+        assert_eq!(Some("a"), slot_map.remove(a));
+        assert_eq!(None, slot_map.remove(a));
+        assert_eq!(vec![b], slot_map.ids().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_slot_map_detects_stale_ids() {
+        let mut slot_map = SlotMap::new();
+        let stale = slot_map.insert(1);
+        slot_map.remove(stale);
+
+        let fresh = slot_map.insert(2);
+        assert_eq!(stale.index(), fresh.index());
+        assert_eq!(None, slot_map.get(stale));
+        assert_eq!(Some(&2), slot_map.get(fresh));
+    }
+}
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+
+/// A copy-on-write cell for read-mostly state. Readers take an `Arc` of the
+/// current value and keep reading it without holding any lock; writers
+/// prepare a modified copy and swap it in, so a slow update never stalls
+/// readers. Updates are serialized with each other.
+pub struct Snapshot<T> {
+    current: RwLock<Arc<T>>,
+    update_lock: Mutex<()>,
+}
+
+impl<T: Clone> Snapshot<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            current: RwLock::new(Arc::new(value)),
+            update_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn load(&self) -> Arc<T> {
+        self.current
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    pub fn store(&self, value: T) {
+        let _update_lock = self
+            .update_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        self.swap(Arc::new(value));
+    }
+
+    pub fn update<R, F: FnOnce(&mut T) -> R>(&self, fun: F) -> R {
+        let _update_lock = self
+            .update_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut next = T::clone(&self.load());
+        let ret = fun(&mut next);
+        self.swap(Arc::new(next));
+
+        ret
+    }
+
+    fn swap(&self, value: Arc<T>) {
+        *self.current.write().unwrap_or_else(PoisonError::into_inner) = value;
+    }
+}
+
+impl<T: Clone + Default> Default for Snapshot<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Snapshot;
+
+    #[test]
+    fn test_snapshot() {
+        let snapshot = Snapshot::new(vec![1, 2]);
+        let before = snapshot.load();
+
+        let len = snapshot.update(|values| {
+            values.push(3);
+            values.len()
+        });
+
+        assert_eq!(3, len);
+        assert_eq!(vec![1, 2], *before);
+        assert_eq!(vec![1, 2, 3], *snapshot.load());
+    }
+}